@@ -0,0 +1,10 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate elma;
+
+use elma::lev::Level;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Level::from_bytes(data);
+});
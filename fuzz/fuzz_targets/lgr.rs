@@ -0,0 +1,10 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate elma;
+
+use elma::lgr::LGR;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = LGR::from_bytes(data);
+});
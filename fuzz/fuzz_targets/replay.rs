@@ -0,0 +1,10 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate elma;
+
+use elma::rec::Replay;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Replay::from_bytes(data);
+});
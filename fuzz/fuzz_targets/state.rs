@@ -0,0 +1,10 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate elma;
+
+use elma::state::State;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = State::from_bytes(data);
+});
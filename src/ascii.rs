@@ -0,0 +1,256 @@
+use super::{
+    geometry::cross,
+    lev::{GravityDirection, Level, Object, ObjectType, Polygon, TopologyError},
+    ElmaError, Position,
+};
+use std::collections::HashMap;
+
+// How far corners where two air cells meet diagonally are pulled apart, in cells.
+const SADDLE_GAP: f64 = 0.05;
+
+/// ASCII grid related errors.
+#[derive(Debug, PartialEq)]
+pub enum AsciiError {
+    /// Character that has no meaning in a grid, with its line and column, both starting at 1.
+    InvalidCharacter {
+        /// Line number.
+        line: usize,
+        /// Column number, in characters.
+        column: usize,
+        /// The character found.
+        character: char,
+    },
+    /// The grid has no air for the level to be in.
+    NoAir,
+    /// The built level is not valid, such as when it has no start or flower.
+    InvalidTopology(TopologyError),
+}
+
+/// Options for building a level from an ASCII grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiOptions {
+    /// Width and height of a grid cell in level units.
+    pub cell_size: f64,
+}
+
+impl Default for AsciiOptions {
+    fn default() -> Self {
+        AsciiOptions { cell_size: 1. }
+    }
+}
+
+impl AsciiOptions {
+    /// Creates new ASCII options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Builds a level from a text grid, one character per cell.
+///
+/// `#` is ground, and space or `.` is air. Objects are placed in the middle of their cell, which
+/// is air: `S` is the start, `F` a flower, `K` a killer and `A` an apple, while `^`, `v`, `<` and
+/// `>` are apples changing gravity up, down, left and right. Everything outside the grid is
+/// ground, as are the missing cells at the end of shorter lines. Blank lines before and after
+/// the grid are ignored, and so is indentation shared by all of its lines.
+///
+/// Neighbouring air cells are merged, so the level gets one polygon for each outline between air
+/// and ground. Cells touching only at a corner stay apart: their polygons are pulled away from
+/// the shared corner by a twentieth of a cell, so no two polygons touch. The grid's top left
+/// corner is at the origin, with rows going down.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::ascii::*;
+/// let level = parse_ascii(
+///     "
+///     #######
+///     #S A F#
+///     #######
+///     ",
+///     &AsciiOptions::new(),
+/// )
+/// .unwrap();
+/// assert_eq!(level.polygons.len(), 1);
+/// assert_eq!(level.objects.len(), 3);
+/// ```
+pub fn parse_ascii(grid: &str, options: &AsciiOptions) -> Result<Level, ElmaError> {
+    let lines: Vec<(usize, &str)> = grid.lines().enumerate().collect();
+    let first = lines.iter().position(|(_, l)| !l.trim().is_empty());
+    let last = lines.iter().rposition(|(_, l)| !l.trim().is_empty());
+    let lines = match (first, last) {
+        (Some(first), Some(last)) => &lines[first..=last],
+        _ => return Err(ElmaError::InvalidAscii(AsciiError::NoAir)),
+    };
+    // Grids are usually indented along with the code around them.
+    let indent = lines
+        .iter()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(_, l)| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let size = options.cell_size;
+    let mut air: Vec<Vec<bool>> = vec![];
+    let mut objects = vec![];
+    for (row, &(line, text)) in lines.iter().enumerate() {
+        let mut cells = vec![];
+        for (column, character) in text.chars().enumerate().skip(indent) {
+            let object_type = match character {
+                '#' => {
+                    cells.push(false);
+                    continue;
+                }
+                ' ' | '.' => {
+                    cells.push(true);
+                    continue;
+                }
+                'S' => ObjectType::Player,
+                'F' => ObjectType::Exit,
+                'K' => ObjectType::Killer,
+                'A' => apple(GravityDirection::None),
+                '^' => apple(GravityDirection::Up),
+                'v' => apple(GravityDirection::Down),
+                '<' => apple(GravityDirection::Left),
+                '>' => apple(GravityDirection::Right),
+                _ => {
+                    return Err(ElmaError::InvalidAscii(AsciiError::InvalidCharacter {
+                        line: line + 1,
+                        column: column + 1,
+                        character,
+                    }))
+                }
+            };
+            let x = cells.len();
+            cells.push(true);
+            objects.push(Object {
+                position: Position::new((x as f64 + 0.5) * size, -(row as f64 + 0.5) * size),
+                object_type,
+            });
+        }
+        air.push(cells);
+    }
+    if !air.iter().any(|row| row.contains(&true)) {
+        return Err(ElmaError::InvalidAscii(AsciiError::NoAir));
+    }
+
+    let mut level = Level::new();
+    level.polygons = outlines(&air)
+        .into_iter()
+        .map(|ring| Polygon {
+            grass: false,
+            vertices: ring
+                .into_iter()
+                .map(|(x, y)| Position::new(x * size, y * size))
+                .collect(),
+        })
+        .collect();
+    level.objects = objects;
+    level
+        .check_topology()
+        .map_err(|e| ElmaError::InvalidAscii(AsciiError::InvalidTopology(e)))?;
+    Ok(level)
+}
+
+fn apple(gravity: GravityDirection) -> ObjectType {
+    ObjectType::Apple {
+        gravity,
+        animation: 1,
+    }
+}
+
+// Outlines between air and ground cells, in cells with y going up. Each outline has its air on
+// the left.
+fn outlines(air: &[Vec<bool>]) -> Vec<Vec<(f64, f64)>> {
+    let is_air = |x: isize, y: isize| {
+        x >= 0
+            && y >= 0
+            && air
+                .get(y as usize)
+                .and_then(|row| row.get(x as usize))
+                .cloned()
+                .unwrap_or(false)
+    };
+
+    // Cell sides with ground on the other side, as corner points (column, -row).
+    let mut edges: Vec<((isize, isize), (isize, isize))> = vec![];
+    for (y, row) in air.iter().enumerate() {
+        for x in 0..row.len() {
+            let (x, y) = (x as isize, y as isize);
+            if !is_air(x, y) {
+                continue;
+            }
+            let (left, right, top, bottom) = (x, x + 1, -y, -y - 1);
+            if !is_air(x, y + 1) {
+                edges.push(((left, bottom), (right, bottom)));
+            }
+            if !is_air(x + 1, y) {
+                edges.push(((right, bottom), (right, top)));
+            }
+            if !is_air(x, y - 1) {
+                edges.push(((right, top), (left, top)));
+            }
+            if !is_air(x - 1, y) {
+                edges.push(((left, top), (left, bottom)));
+            }
+        }
+    }
+
+    let mut starting: HashMap<(isize, isize), Vec<usize>> = HashMap::new();
+    for (n, edge) in edges.iter().enumerate() {
+        starting.entry(edge.0).or_default().push(n);
+    }
+    let direction = |n: usize| {
+        let (from, to) = edges[n];
+        ((to.0 - from.0) as f64, (to.1 - from.1) as f64)
+    };
+    // Where two air cells meet at a corner, two edges leave it; turning left keeps following
+    // the same cell.
+    let next: Vec<usize> = (0..edges.len())
+        .map(|n| {
+            let (dx, dy) = direction(n);
+            let out = &starting[&edges[n].1];
+            *out.iter()
+                .find(|&&m| {
+                    let (ex, ey) = direction(m);
+                    cross(dx, dy, ex, ey) > 0.
+                })
+                .unwrap_or(&out[0])
+        })
+        .collect();
+
+    let mut visited = vec![false; edges.len()];
+    let mut rings = vec![];
+    for start in 0..edges.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut ring = vec![];
+        let mut previous = start;
+        let mut edge = next[start];
+        loop {
+            visited[edge] = true;
+            let (dx, dy) = direction(previous);
+            let (ex, ey) = direction(edge);
+            // Only corners are kept.
+            if (dx, dy) != (ex, ey) {
+                let (x, y) = edges[edge].0;
+                let (mut x, mut y) = (x as f64, y as f64);
+                if starting[&edges[edge].0].len() > 1 {
+                    // Pull the corner diagonally into this cell.
+                    x += (ex - dx) * SADDLE_GAP;
+                    y += (ey - dy) * SADDLE_GAP;
+                }
+                ring.push((x, y));
+            }
+            if edge == start {
+                break;
+            }
+            previous = edge;
+            edge = next[edge];
+        }
+        rings.push(ring);
+    }
+    rings
+}
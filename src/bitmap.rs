@@ -0,0 +1,525 @@
+use super::{
+    constants::MAX_POLYGONS,
+    lev::{Level, Object, ObjectType, Polygon},
+    ElmaError, Position,
+};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+
+// Free space needed around the start object, in level units.
+const START_CLEARANCE: f64 = 1.;
+
+/// Bitmap related errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitmapError {
+    /// Not a PBM or PGM file, with the magic bytes found.
+    UnsupportedFormat(Vec<u8>),
+    /// Missing or invalid width, height or maximum value.
+    InvalidHeader,
+    /// Invalid pixel value in a plain text file, with its byte offset.
+    InvalidPixel(usize),
+    /// The bitmap has no air for the level to be in.
+    NoAir,
+}
+
+/// Options for tracing a bitmap into polygons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceOptions {
+    /// Size of the longer side of the bitmap in level units.
+    pub max_size: f64,
+    /// Largest distance a simplified outline may deviate from the traced one, in pixels.
+    pub tolerance: f64,
+    /// Outlines enclosing less than this area are dropped, in square pixels.
+    pub min_area: f64,
+    /// Treat light pixels as ground and dark pixels as air.
+    pub invert: bool,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            max_size: 188.,
+            tolerance: 0.7,
+            min_area: 2.,
+            invert: false,
+        }
+    }
+}
+
+impl TraceOptions {
+    /// Creates new trace options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Black-and-white image, where filled (dark) pixels are ground and empty pixels are air.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap {
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// Pixels row by row, starting from the top left. `true` is filled.
+    pub pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Creates an empty bitmap.
+    pub fn new(width: usize, height: usize) -> Self {
+        Bitmap {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    /// Loads a PBM or PGM file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use elma::bitmap::*;
+    /// let bitmap = Bitmap::load("terrain.pbm").unwrap();
+    /// ```
+    pub fn load<P: Into<PathBuf>>(path: P) -> Result<Self, ElmaError> {
+        let buffer = fs::read(path.into())?;
+        Bitmap::from_bytes(buffer)
+    }
+
+    /// Reads a bitmap from PBM (`P1`, `P4`) or PGM (`P2`, `P5`) bytes. Grey pixels darker than
+    /// half the maximum value are filled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::bitmap::*;
+    /// let bitmap = Bitmap::from_bytes("P1\n3 2\n1 1 1\n0 0 1\n").unwrap();
+    /// assert_eq!(bitmap.pixels, [true, true, true, false, false, true]);
+    /// ```
+    pub fn from_bytes<B: AsRef<[u8]>>(buffer: B) -> Result<Self, ElmaError> {
+        parse_pnm(buffer.as_ref())
+    }
+
+    /// Returns whether the pixel at `x`, `y` is filled. Pixels outside the bitmap are filled.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x >= self.width || y >= self.height || self.pixels[y * self.width + x]
+    }
+
+    /// Fills or clears the pixel at `x`, `y`.
+    pub fn set(&mut self, x: usize, y: usize, filled: bool) {
+        self.pixels[y * self.width + x] = filled;
+    }
+
+    /// Traces the outlines between ground and air into polygons.
+    ///
+    /// Outlines are found with marching squares over the pixel centres and simplified with the
+    /// Douglas-Peucker algorithm. Everything outside the bitmap counts as ground, so all outlines
+    /// are closed. The bitmap is scaled so its longer side is `max_size` level units, with its
+    /// top left corner at the origin. Small outlines are dropped and at most the 1000 largest
+    /// are kept.
+    pub fn trace(&self, options: &TraceOptions) -> Vec<Polygon> {
+        let scale = self.scale(options);
+        let mut rings: Vec<(f64, Vec<(f64, f64)>)> = self
+            .contours(options.invert)
+            .into_iter()
+            .map(|ring| simplify(&ring, options.tolerance))
+            .filter(|ring| ring.len() >= 3)
+            .map(|ring| (ring_area(&ring).abs(), ring))
+            .filter(|&(area, _)| area >= options.min_area)
+            .collect();
+        if rings.len() > MAX_POLYGONS {
+            rings.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+            rings.truncate(MAX_POLYGONS);
+        }
+
+        let width = self.width as f64 * scale;
+        let height = self.height as f64 * scale;
+        rings
+            .into_iter()
+            .map(|(_, ring)| Polygon {
+                grass: false,
+                vertices: ring
+                    .into_iter()
+                    .map(|(x, y)| Position::new((x * scale).min(width), -(y * scale).min(height)))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Creates a level from the bitmap, with polygons from `trace` and the start and exit
+    /// placed at the left and right ends of the largest open area.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::bitmap::*;
+    /// let mut bitmap = Bitmap::new(40, 20);
+    /// for x in 0..40 {
+    ///     bitmap.set(x, 19, true);
+    /// }
+    /// let level = bitmap.to_level(&TraceOptions::new()).unwrap();
+    /// assert!(level.check_topology().is_ok());
+    /// ```
+    pub fn to_level(&self, options: &TraceOptions) -> Result<Level, ElmaError> {
+        let polygons = self.trace(options);
+        if polygons.is_empty() {
+            return Err(ElmaError::InvalidBitmap(BitmapError::NoAir));
+        }
+
+        let scale = self.scale(options);
+        let (start, exit) = self.object_spots(options.invert, scale);
+        let position = |(x, y): (usize, usize)| {
+            Position::new((x as f64 + 0.5) * scale, -(y as f64 + 0.5) * scale)
+        };
+
+        let mut level = Level::new();
+        level.polygons = polygons;
+        level.objects = vec![
+            Object {
+                position: position(start),
+                object_type: ObjectType::Player,
+            },
+            Object {
+                position: position(exit),
+                object_type: ObjectType::Exit,
+            },
+        ];
+        Ok(level)
+    }
+
+    fn scale(&self, options: &TraceOptions) -> f64 {
+        options.max_size / self.width.max(self.height).max(1) as f64
+    }
+
+    fn is_ground(&self, x: usize, y: usize, invert: bool) -> bool {
+        x >= self.width || y >= self.height || self.pixels[y * self.width + x] != invert
+    }
+
+    // Ground test on the sample grid, which has a ring of ground around the bitmap.
+    fn sample(&self, x: usize, y: usize, invert: bool) -> bool {
+        x == 0 || y == 0 || self.is_ground(x - 1, y - 1, invert)
+    }
+
+    // Closed outlines in pixel coordinates, with ground on the same side of every outline.
+    fn contours(&self, invert: bool) -> Vec<Vec<(f64, f64)>> {
+        // Edge midpoints on the sample grid, in doubled coordinates so they are integers.
+        let mut next: BTreeMap<(usize, usize), (usize, usize)> = BTreeMap::new();
+        for y in 0..=self.height {
+            for x in 0..=self.width {
+                let corners = [
+                    self.sample(x, y, invert),
+                    self.sample(x + 1, y, invert),
+                    self.sample(x + 1, y + 1, invert),
+                    self.sample(x, y + 1, invert),
+                ];
+                let edges = [
+                    (2 * x + 1, 2 * y),
+                    (2 * x + 2, 2 * y + 1),
+                    (2 * x + 1, 2 * y + 2),
+                    (2 * x, 2 * y + 1),
+                ];
+                let crosses = |k: usize| corners[k] != corners[(k + 1) % 4];
+                // Going clockwise, each crossing from air into ground connects to the following
+                // crossing. In saddle cells this keeps diagonal ground pixels apart.
+                for k in (0..4).filter(|&k| crosses(k) && !corners[k]) {
+                    let to = (k + 1..k + 4).map(|j| j % 4).find(|&j| crosses(j)).unwrap();
+                    next.insert(edges[k], edges[to]);
+                }
+            }
+        }
+
+        let mut rings = vec![];
+        while let Some((&first, _)) = next.iter().next() {
+            let mut ring = vec![];
+            let mut key = first;
+            while let Some(to) = next.remove(&key) {
+                // Sample (s) is pixel (s - 1), whose centre is at s - 0.5.
+                ring.push((key.0 as f64 / 2. - 0.5, key.1 as f64 / 2. - 0.5));
+                key = to;
+            }
+            rings.push(ring);
+        }
+        rings
+    }
+
+    // Start and exit pixels: the left and right ends of the largest area with room for a bike.
+    fn object_spots(&self, invert: bool, scale: f64) -> ((usize, usize), (usize, usize)) {
+        let (width, height) = (self.width, self.height);
+        // Chessboard distance from every pixel to the nearest ground pixel.
+        let mut clearance = vec![usize::MAX; width * height];
+        let mut queue = VecDeque::new();
+        for y in 0..height {
+            for x in 0..width {
+                if self.is_ground(x, y, invert) {
+                    clearance[y * width + x] = 0;
+                    queue.push_back((x, y));
+                } else if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                    clearance[y * width + x] = 1;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            let distance = clearance[y * width + x] + 1;
+            for (nx, ny) in neighbours(x, y, width, height, true) {
+                if clearance[ny * width + nx] > distance {
+                    clearance[ny * width + nx] = distance;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        let best = clearance.iter().cloned().max().unwrap_or(0);
+        let needed = ((START_CLEARANCE / scale + 0.5).ceil() as usize).min(best);
+
+        // Largest 4-connected area of pixels with enough room.
+        let mut area = vec![false; width * height];
+        let mut largest: Vec<(usize, usize)> = vec![];
+        for start in 0..width * height {
+            if area[start] || clearance[start] < needed {
+                continue;
+            }
+            let mut component = vec![];
+            let mut stack = vec![(start % width, start / width)];
+            area[start] = true;
+            while let Some((x, y)) = stack.pop() {
+                component.push((x, y));
+                for (nx, ny) in neighbours(x, y, width, height, false) {
+                    let n = ny * width + nx;
+                    if !area[n] && clearance[n] >= needed {
+                        area[n] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+            if component.len() > largest.len() {
+                largest = component;
+            }
+        }
+
+        // Leftmost and rightmost spots, as low as possible.
+        let start = largest
+            .iter()
+            .min_by_key(|&&(x, y)| (x, height - y))
+            .unwrap();
+        let exit = largest.iter().max_by_key(|&&(x, y)| (x, y)).unwrap();
+        (*start, *exit)
+    }
+}
+
+fn neighbours(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    diagonal: bool,
+) -> Vec<(usize, usize)> {
+    let mut result = vec![];
+    for dy in -1i64..=1 {
+        for dx in -1i64..=1 {
+            if (dx == 0 && dy == 0) || (!diagonal && dx != 0 && dy != 0) {
+                continue;
+            }
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if nx >= 0 && ny >= 0 && (nx as usize) < width && (ny as usize) < height {
+                result.push((nx as usize, ny as usize));
+            }
+        }
+    }
+    result
+}
+
+// Douglas-Peucker simplification of a closed ring, split at the point farthest from the first.
+fn simplify(ring: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    if ring.len() < 4 {
+        return ring.to_vec();
+    }
+    let first = ring[0];
+    let far = (1..ring.len())
+        .max_by(|&a, &b| {
+            let da = (ring[a].0 - first.0).hypot(ring[a].1 - first.1);
+            let db = (ring[b].0 - first.0).hypot(ring[b].1 - first.1);
+            da.partial_cmp(&db).unwrap()
+        })
+        .unwrap();
+
+    let mut result = simplify_open(&ring[..=far], tolerance);
+    result.pop();
+    let mut second: Vec<_> = ring[far..].to_vec();
+    second.push(first);
+    result.extend(simplify_open(&second, tolerance));
+    result.pop();
+    result
+}
+
+fn simplify_open(points: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    let last = points.len() - 1;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[last] = true;
+    let mut stack = vec![(0, last)];
+    while let Some((from, to)) = stack.pop() {
+        let (a, b) = (points[from], points[to]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let length = dx.hypot(dy);
+        let mut farthest = (0., from);
+        for (n, p) in points.iter().enumerate().take(to).skip(from + 1) {
+            let distance = if length == 0. {
+                (p.0 - a.0).hypot(p.1 - a.1)
+            } else {
+                ((p.0 - a.0) * dy - (p.1 - a.1) * dx).abs() / length
+            };
+            if distance > farthest.0 {
+                farthest = (distance, n);
+            }
+        }
+        if farthest.0 > tolerance {
+            keep[farthest.1] = true;
+            stack.push((from, farthest.1));
+            stack.push((farthest.1, to));
+        }
+    }
+    points
+        .iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+fn ring_area(ring: &[(f64, f64)]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (ring[i], ring[(i + 1) % n]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+fn parse_pnm(buffer: &[u8]) -> Result<Bitmap, ElmaError> {
+    let invalid = |error| ElmaError::InvalidBitmap(error);
+    let magic = buffer.get(..2).unwrap_or(buffer);
+    let (plain, grey) = match magic {
+        b"P1" => (true, false),
+        b"P2" => (true, true),
+        b"P4" => (false, false),
+        b"P5" => (false, true),
+        _ => return Err(invalid(BitmapError::UnsupportedFormat(magic.to_vec()))),
+    };
+
+    let mut pos = 2;
+    let mut header = [0usize; 3];
+    let fields = if grey { 3 } else { 2 };
+    for field in header.iter_mut().take(fields) {
+        let (value, end) =
+            header_number(buffer, pos).ok_or_else(|| invalid(BitmapError::InvalidHeader))?;
+        *field = value;
+        pos = end;
+    }
+    let (width, height, max_value) = (header[0], header[1], header[2]);
+    let count = width
+        .checked_mul(height)
+        .filter(|&count| count > 0)
+        .ok_or_else(|| invalid(BitmapError::InvalidHeader))?;
+    if grey && (max_value == 0 || max_value > 65535) {
+        return Err(invalid(BitmapError::InvalidHeader));
+    }
+    let eof = |offset| ElmaError::UnexpectedEof {
+        offset,
+        section: "pixels",
+    };
+    // Grey values darker than half the maximum are filled.
+    let dark = |value: usize| value * 2 < max_value;
+
+    let mut pixels = Vec::with_capacity(count.min(buffer.len() * 8));
+    if plain {
+        while pixels.len() < count {
+            pos = skip_space(buffer, pos);
+            if pos >= buffer.len() {
+                return Err(eof(pos));
+            }
+            if grey {
+                let end = pos
+                    + buffer[pos..]
+                        .iter()
+                        .take_while(|b| b.is_ascii_digit())
+                        .count();
+                let value = ::std::str::from_utf8(&buffer[pos..end])
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(|| invalid(BitmapError::InvalidPixel(pos)))?;
+                pixels.push(dark(value));
+                pos = end;
+            } else {
+                // Plain PBM digits need no separators.
+                match buffer[pos] {
+                    b'0' => pixels.push(false),
+                    b'1' => pixels.push(true),
+                    _ => return Err(invalid(BitmapError::InvalidPixel(pos))),
+                }
+                pos += 1;
+            }
+        }
+    } else {
+        // A single whitespace byte separates the header from binary data.
+        pos += 1;
+        let row_bytes = if grey {
+            width.saturating_mul(if max_value > 255 { 2 } else { 1 })
+        } else {
+            width.div_ceil(8)
+        };
+        for y in 0..height {
+            let start = pos.saturating_add(y.saturating_mul(row_bytes));
+            let row = buffer
+                .get(start..start.saturating_add(row_bytes))
+                .ok_or_else(|| eof(start))?;
+            for x in 0..width {
+                pixels.push(if !grey {
+                    row[x / 8] & (0x80 >> (x % 8)) != 0
+                } else if max_value > 255 {
+                    dark(usize::from(row[2 * x]) << 8 | usize::from(row[2 * x + 1]))
+                } else {
+                    dark(usize::from(row[x]))
+                });
+            }
+        }
+    }
+
+    Ok(Bitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn skip_space(buffer: &[u8], mut pos: usize) -> usize {
+    while pos < buffer.len() {
+        match buffer[pos] {
+            b'#' => {
+                while pos < buffer.len() && buffer[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
+            _ => break,
+        }
+    }
+    pos
+}
+
+fn header_number(buffer: &[u8], pos: usize) -> Option<(usize, usize)> {
+    let start = skip_space(buffer, pos);
+    let digits = buffer[start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    let value = ::std::str::from_utf8(&buffer[start..start + digits])
+        .ok()?
+        .parse()
+        .ok()?;
+    Some((value, start + digits))
+}
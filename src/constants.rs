@@ -2,6 +2,12 @@
 pub const HEAD_DIAMETER: f64 = 0.476;
 /// Radius of player head.
 pub const HEAD_RADIUS: f64 = 0.238;
+/// Most objects a level can have.
+pub const MAX_OBJECTS: usize = 252;
+/// Most pictures a level can have.
+pub const MAX_PICTURES: usize = 5000;
+/// Most polygons a level can have.
+pub const MAX_POLYGONS: usize = 1000;
 /// Diameter of objects (and wheels).
 pub const OBJECT_DIAMETER: f64 = 0.8;
 /// Radius of objects (and wheels).
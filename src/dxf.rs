@@ -0,0 +1,471 @@
+use super::{
+    constants::OBJECT_RADIUS,
+    lev::{GravityDirection, Level, Object, ObjectType, Polygon},
+    ElmaError, Position,
+};
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+// Polyline flag for closed polylines.
+const CLOSED: i32 = 1;
+// Polyline flags for 3D polylines and polyface meshes, which are not flat outlines.
+const NOT_FLAT: i32 = 8 | 16 | 64;
+
+/// DXF import errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DxfError {
+    /// Group code that is not a number, with its line number.
+    InvalidGroupCode(usize),
+    /// Group value that should be a number but is not, with its line number.
+    InvalidNumber(usize),
+    /// Group code without a value on the last line.
+    MissingValue(usize),
+}
+
+/// Options for `import_dxf`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxfOptions {
+    /// Level units per drawing unit.
+    pub scale: f64,
+    /// Largest allowed distance between an arc and the polygon edges replacing it, in level
+    /// units.
+    pub tolerance: f64,
+}
+
+impl Default for DxfOptions {
+    fn default() -> Self {
+        DxfOptions {
+            scale: 1.,
+            tolerance: 0.05,
+        }
+    }
+}
+
+impl DxfOptions {
+    /// Creates new DXF options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Polygons and objects imported from a DXF drawing.
+#[derive(Debug, Default, PartialEq)]
+pub struct DxfImport {
+    /// Polygons from polylines and circles.
+    pub polygons: Vec<Polygon>,
+    /// Objects from circles and points on object layers.
+    pub objects: Vec<Object>,
+}
+
+impl DxfImport {
+    /// Creates a new level with the imported polygons and objects.
+    pub fn into_level(self) -> Level {
+        let mut level = Level::new();
+        level.polygons = self.polygons;
+        level.objects = self.objects;
+        level
+    }
+}
+
+// Entity with its groups as (code, value, line number).
+struct Entity<'a> {
+    kind: &'a str,
+    groups: Vec<(i32, &'a str, usize)>,
+}
+
+impl<'a> Entity<'a> {
+    fn layer(&self) -> &'a str {
+        self.groups.iter().find(|g| g.0 == 8).map_or("0", |g| g.1)
+    }
+
+    fn number(&self, code: i32) -> Result<Option<f64>, ElmaError> {
+        match self.groups.iter().find(|g| g.0 == code) {
+            Some(&(_, value, line)) => parse_number(value, line).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn flags(&self) -> Result<i32, ElmaError> {
+        Ok(self.number(70)?.unwrap_or(0.) as i32)
+    }
+}
+
+/// Loads a DXF file and imports it with `import_dxf`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use elma::dxf::*;
+/// let level = load_dxf("drawing.dxf", &DxfOptions::new()).unwrap().into_level();
+/// ```
+pub fn load_dxf<P: Into<PathBuf>>(path: P, options: &DxfOptions) -> Result<DxfImport, ElmaError> {
+    let source = fs::read_to_string(path.into())?;
+    import_dxf(&source, options)
+}
+
+/// Imports the entities of an ASCII DXF drawing as level polygons and objects.
+///
+/// `LWPOLYLINE` and `POLYLINE` entities become polygons, with arc segments flattened to within
+/// `tolerance`; open polylines are closed. Polygons on layers whose name starts with `GRASS`
+/// are grass polygons. `CIRCLE` and `POINT` entities on the layers `PLAYER` (or `START`),
+/// `EXIT` (or `FLOWER`), `KILLER` and `APPLE` become objects. Apple layers may carry the
+/// gravity and animation number, as in `APPLE_UP_3`. Circles on other layers become round
+/// polygons. Layer names are not case sensitive and other entities are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::dxf::*;
+/// # use elma::lev::*;
+/// let dxf = export_dxf(&Level::new());
+/// let import = import_dxf(&dxf, &DxfOptions::new()).unwrap();
+/// assert_eq!(import.polygons, Level::new().polygons);
+/// assert_eq!(import.objects, Level::new().objects);
+/// ```
+pub fn import_dxf(source: &str, options: &DxfOptions) -> Result<DxfImport, ElmaError> {
+    let entities = read_entities(source)?;
+    let scale = options.scale;
+    let mut import = DxfImport::default();
+
+    let mut n = 0;
+    while n < entities.len() {
+        let entity = &entities[n];
+        n += 1;
+        let layer = entity.layer().to_ascii_uppercase();
+        match entity.kind {
+            "LWPOLYLINE" => {
+                let mut vertices: Vec<(f64, f64, f64)> = vec![];
+                for &(code, value, line) in &entity.groups {
+                    match code {
+                        10 => vertices.push((parse_number(value, line)?, 0., 0.)),
+                        20 | 42 => {
+                            if let Some(vertex) = vertices.last_mut() {
+                                let number = parse_number(value, line)?;
+                                if code == 20 {
+                                    vertex.1 = number;
+                                } else {
+                                    vertex.2 = number;
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                push_polyline(&mut import, &layer, &vertices, options);
+            }
+            "POLYLINE" => {
+                let flags = entity.flags()?;
+                let mut vertices = vec![];
+                while n < entities.len() && entities[n].kind == "VERTEX" {
+                    let vertex = &entities[n];
+                    vertices.push((
+                        vertex.number(10)?.unwrap_or(0.),
+                        vertex.number(20)?.unwrap_or(0.),
+                        vertex.number(42)?.unwrap_or(0.),
+                    ));
+                    n += 1;
+                }
+                if flags & NOT_FLAT == 0 {
+                    push_polyline(&mut import, &layer, &vertices, options);
+                }
+            }
+            "CIRCLE" | "POINT" => {
+                let center = Position::new(
+                    entity.number(10)?.unwrap_or(0.) * scale,
+                    entity.number(20)?.unwrap_or(0.) * scale,
+                );
+                if let Some(object_type) = object_type(&layer) {
+                    import.objects.push(Object {
+                        position: center,
+                        object_type,
+                    });
+                } else if entity.kind == "CIRCLE" {
+                    let radius = entity.number(40)?.unwrap_or(0.) * scale;
+                    let segments = arc_segments(radius, 2. * PI, options.tolerance);
+                    let vertices: Vec<_> = (0..segments)
+                        .map(|k| {
+                            let angle = 2. * PI * k as f64 / segments as f64;
+                            Position::new(
+                                center.x + radius * angle.cos(),
+                                center.y + radius * angle.sin(),
+                            )
+                        })
+                        .collect();
+                    if radius > 0. {
+                        import.polygons.push(Polygon {
+                            grass: layer.starts_with("GRASS"),
+                            vertices,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(import)
+}
+
+/// Writes the polygons and objects of a level as an ASCII DXF drawing.
+///
+/// Polygons become closed `POLYLINE` entities on the `GROUND` or `GRASS` layer, and objects
+/// become `CIRCLE` entities on the `PLAYER`, `EXIT`, `KILLER` and `APPLE` layers, using the
+/// same layer names `import_dxf` reads. Drawing units are level units.
+pub fn export_dxf(level: &Level) -> String {
+    let mut layers = vec![];
+    for polygon in &level.polygons {
+        layers.push(if polygon.grass { "GRASS" } else { "GROUND" }.to_string());
+    }
+    for object in &level.objects {
+        layers.push(object_layer(&object.object_type));
+    }
+    let mut used = layers.clone();
+    used.sort();
+    used.dedup();
+
+    let mut dxf = String::new();
+    group(&mut dxf, 0, "SECTION");
+    group(&mut dxf, 2, "HEADER");
+    group(&mut dxf, 9, "$ACADVER");
+    group(&mut dxf, 1, "AC1009");
+    group(&mut dxf, 0, "ENDSEC");
+
+    group(&mut dxf, 0, "SECTION");
+    group(&mut dxf, 2, "TABLES");
+    group(&mut dxf, 0, "TABLE");
+    group(&mut dxf, 2, "LAYER");
+    group(&mut dxf, 70, used.len());
+    for layer in &used {
+        let color = match layer.split('_').next().unwrap_or("") {
+            "GRASS" => 3,
+            "PLAYER" => 4,
+            "EXIT" => 2,
+            "APPLE" => 1,
+            "KILLER" => 6,
+            _ => 7,
+        };
+        group(&mut dxf, 0, "LAYER");
+        group(&mut dxf, 2, layer);
+        group(&mut dxf, 70, 0);
+        group(&mut dxf, 62, color);
+        group(&mut dxf, 6, "CONTINUOUS");
+    }
+    group(&mut dxf, 0, "ENDTAB");
+    group(&mut dxf, 0, "ENDSEC");
+
+    group(&mut dxf, 0, "SECTION");
+    group(&mut dxf, 2, "ENTITIES");
+    let mut layers = layers.iter();
+    for polygon in &level.polygons {
+        let layer = layers.next().unwrap();
+        group(&mut dxf, 0, "POLYLINE");
+        group(&mut dxf, 8, layer);
+        group(&mut dxf, 66, 1);
+        group(&mut dxf, 70, CLOSED);
+        for vertex in &polygon.vertices {
+            group(&mut dxf, 0, "VERTEX");
+            group(&mut dxf, 8, layer);
+            group(&mut dxf, 10, vertex.x);
+            group(&mut dxf, 20, vertex.y);
+        }
+        group(&mut dxf, 0, "SEQEND");
+        group(&mut dxf, 8, layer);
+    }
+    for object in &level.objects {
+        group(&mut dxf, 0, "CIRCLE");
+        group(&mut dxf, 8, layers.next().unwrap());
+        group(&mut dxf, 10, object.position.x);
+        group(&mut dxf, 20, object.position.y);
+        group(&mut dxf, 40, OBJECT_RADIUS);
+    }
+    group(&mut dxf, 0, "ENDSEC");
+    group(&mut dxf, 0, "EOF");
+    dxf
+}
+
+/// Writes a level to a DXF file with `export_dxf`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use elma::dxf::*;
+/// # use elma::lev::*;
+/// save_dxf(&Level::new(), "level.dxf").unwrap();
+/// ```
+pub fn save_dxf<P: Into<PathBuf>>(level: &Level, path: P) -> Result<(), ElmaError> {
+    fs::write(path.into(), export_dxf(level))?;
+    Ok(())
+}
+
+fn group<T: ::std::fmt::Display>(dxf: &mut String, code: i32, value: T) {
+    let _ = write!(dxf, "{:>3}\n{}\n", code, value);
+}
+
+fn parse_number(value: &str, line: usize) -> Result<f64, ElmaError> {
+    value
+        .parse()
+        .map_err(|_| ElmaError::InvalidDxf(DxfError::InvalidNumber(line)))
+}
+
+// Entities of the ENTITIES section.
+fn read_entities(source: &str) -> Result<Vec<Entity<'_>>, ElmaError> {
+    let lines: Vec<&str> = source.lines().map(|l| l.trim()).collect();
+    let mut entities = vec![];
+    let mut section: Option<&str> = None;
+    let mut current: Option<Entity> = None;
+
+    for pair in (0..lines.len()).step_by(2) {
+        // Trailing blank lines after EOF are common.
+        if pair + 1 >= lines.len() {
+            if lines[pair].is_empty() {
+                break;
+            }
+            return Err(ElmaError::InvalidDxf(DxfError::MissingValue(pair + 1)));
+        }
+        let code: i32 = lines[pair]
+            .parse()
+            .map_err(|_| ElmaError::InvalidDxf(DxfError::InvalidGroupCode(pair + 1)))?;
+        let value = lines[pair + 1];
+
+        if code == 0 {
+            entities.extend(current.take());
+            match value {
+                "EOF" => break,
+                "ENDSEC" => section = None,
+                "SECTION" => section = Some(""),
+                kind if section == Some("ENTITIES") => {
+                    current = Some(Entity {
+                        kind,
+                        groups: vec![],
+                    })
+                }
+                _ => {}
+            }
+        } else if code == 2 && section == Some("") {
+            section = Some(value);
+        } else if let Some(entity) = current.as_mut() {
+            entity.groups.push((code, value, pair + 2));
+        }
+    }
+    entities.extend(current);
+    Ok(entities)
+}
+
+fn push_polyline(
+    import: &mut DxfImport,
+    layer: &str,
+    vertices: &[(f64, f64, f64)],
+    options: &DxfOptions,
+) {
+    let scale = options.scale;
+    let mut ring: Vec<Position<f64>> = vec![];
+    for (n, &(x, y, bulge)) in vertices.iter().enumerate() {
+        let from = Position::new(x * scale, y * scale);
+        ring.push(from);
+        if bulge != 0. {
+            let next = vertices[(n + 1) % vertices.len()];
+            let to = Position::new(next.0 * scale, next.1 * scale);
+            ring.extend(flatten_bulge(&from, &to, bulge, options.tolerance));
+        }
+    }
+    if ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+        ring.pop();
+    }
+    if ring.len() > 2 {
+        import.polygons.push(Polygon {
+            grass: layer.starts_with("GRASS"),
+            vertices: ring,
+        });
+    }
+}
+
+// Points strictly between `from` and `to` along an arc segment. The bulge is the tangent of a
+// quarter of the arc's angle, positive for counter-clockwise arcs.
+fn flatten_bulge(
+    from: &Position<f64>,
+    to: &Position<f64>,
+    bulge: f64,
+    tolerance: f64,
+) -> Vec<Position<f64>> {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let chord = dx.hypot(dy);
+    if chord == 0. {
+        return vec![];
+    }
+    let angle = 4. * bulge.atan();
+    let radius = chord / (2. * (angle / 2.).sin());
+    // Distance from the chord midpoint to the centre, to the left of the chord.
+    let offset = radius * (angle / 2.).cos();
+    let center = Position::new(
+        (from.x + to.x) / 2. - dy / chord * offset,
+        (from.y + to.y) / 2. + dx / chord * offset,
+    );
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let radius = radius.abs();
+    let segments = arc_segments(radius, angle.abs(), tolerance);
+    (1..segments)
+        .map(|k| {
+            let a = start + angle * k as f64 / segments as f64;
+            Position::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+        })
+        .collect()
+}
+
+// Number of straight segments keeping an arc within `tolerance`.
+fn arc_segments(radius: f64, angle: f64, tolerance: f64) -> usize {
+    let step = if tolerance > 0. && tolerance < radius {
+        2. * (1. - tolerance / radius).acos()
+    } else {
+        PI / 2.
+    };
+    let min = if angle >= 2. * PI { 3 } else { 1 };
+    ((angle / step).ceil() as usize).clamp(min, 1000)
+}
+
+fn object_type(layer: &str) -> Option<ObjectType> {
+    let mut parts = layer.split('_');
+    match parts.next()? {
+        "PLAYER" | "START" => Some(ObjectType::Player),
+        "EXIT" | "FLOWER" => Some(ObjectType::Exit),
+        "KILLER" => Some(ObjectType::Killer),
+        "APPLE" => {
+            let mut gravity = GravityDirection::None;
+            let mut animation = 1;
+            for part in parts {
+                match part {
+                    "NONE" => gravity = GravityDirection::None,
+                    "UP" => gravity = GravityDirection::Up,
+                    "DOWN" => gravity = GravityDirection::Down,
+                    "LEFT" => gravity = GravityDirection::Left,
+                    "RIGHT" => gravity = GravityDirection::Right,
+                    number => animation = number.parse().unwrap_or(animation),
+                }
+            }
+            Some(ObjectType::Apple { gravity, animation })
+        }
+        _ => None,
+    }
+}
+
+fn object_layer(object_type: &ObjectType) -> String {
+    match *object_type {
+        ObjectType::Player => "PLAYER".into(),
+        ObjectType::Exit => "EXIT".into(),
+        ObjectType::Killer => "KILLER".into(),
+        ObjectType::Apple { gravity, animation } => {
+            let mut layer = String::from("APPLE");
+            match gravity {
+                GravityDirection::None => {}
+                GravityDirection::Up => layer.push_str("_UP"),
+                GravityDirection::Down => layer.push_str("_DOWN"),
+                GravityDirection::Left => layer.push_str("_LEFT"),
+                GravityDirection::Right => layer.push_str("_RIGHT"),
+            }
+            if animation != 1 {
+                let _ = write!(layer, "_{}", animation);
+            }
+            layer
+        }
+    }
+}
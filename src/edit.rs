@@ -0,0 +1,457 @@
+use super::{
+    lev::{GravityDirection, Level, Object, ObjectType, Picture, Polygon},
+    Position,
+};
+use std::mem;
+
+/// Edit related errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// No polygon at this index.
+    NoPolygon(usize),
+    /// No vertex at this index in the polygon.
+    NoVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Vertex index.
+        vertex: usize,
+    },
+    /// No object at this index.
+    NoObject(usize),
+    /// No picture at this index.
+    NoPicture(usize),
+    /// Gravity can only be set on apples.
+    NotAnApple(usize),
+}
+
+/// Reversible change to a level.
+///
+/// Applying an edit returns the edit that undoes it. Insert indices may be one past the last
+/// element to append.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Inserts a vertex into a polygon.
+    InsertVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Index the vertex will have.
+        vertex: usize,
+        /// Position of the vertex.
+        position: Position<f64>,
+    },
+    /// Moves a polygon vertex.
+    MoveVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Vertex index.
+        vertex: usize,
+        /// New position.
+        position: Position<f64>,
+    },
+    /// Removes a vertex from a polygon.
+    RemoveVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Vertex index.
+        vertex: usize,
+    },
+    /// Inserts a polygon.
+    InsertPolygon {
+        /// Index the polygon will have.
+        index: usize,
+        /// The polygon.
+        polygon: Polygon,
+    },
+    /// Moves every vertex of a polygon.
+    MovePolygon {
+        /// Polygon index.
+        index: usize,
+        /// How far to move the polygon.
+        offset: Position<f64>,
+    },
+    /// Removes a polygon.
+    RemovePolygon {
+        /// Polygon index.
+        index: usize,
+    },
+    /// Inserts an object.
+    InsertObject {
+        /// Index the object will have.
+        index: usize,
+        /// The object.
+        object: Object,
+    },
+    /// Moves an object.
+    MoveObject {
+        /// Object index.
+        index: usize,
+        /// New position.
+        position: Position<f64>,
+    },
+    /// Removes an object.
+    RemoveObject {
+        /// Object index.
+        index: usize,
+    },
+    /// Changes the type of an object.
+    SetObjectType {
+        /// Object index.
+        index: usize,
+        /// New type.
+        object_type: ObjectType,
+    },
+    /// Changes the gravity of an apple, keeping its animation.
+    SetGravity {
+        /// Object index.
+        index: usize,
+        /// New gravity.
+        gravity: GravityDirection,
+    },
+    /// Inserts a picture.
+    InsertPicture {
+        /// Index the picture will have.
+        index: usize,
+        /// The picture.
+        picture: Picture,
+    },
+    /// Moves a picture.
+    MovePicture {
+        /// Picture index.
+        index: usize,
+        /// New position.
+        position: Position<f64>,
+    },
+    /// Removes a picture.
+    RemovePicture {
+        /// Picture index.
+        index: usize,
+    },
+    /// Changes the level title.
+    SetTitle(String),
+    /// Changes the LGR name.
+    SetLgr(String),
+    /// Changes the ground texture name.
+    SetGround(String),
+    /// Changes the sky texture name.
+    SetSky(String),
+    /// Several edits applied in order, all or none.
+    Group(Vec<Edit>),
+}
+
+impl Edit {
+    /// Applies the edit to a level and returns the edit that reverts it. On error the level is
+    /// left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::edit::*;
+    /// # use elma::lev::*;
+    /// # use elma::Position;
+    /// let mut level = Level::new();
+    /// let edit = Edit::MoveObject {
+    ///     index: 0,
+    ///     position: Position::new(5., 5.),
+    /// };
+    /// let undo = edit.apply(&mut level).unwrap();
+    /// assert_eq!(level.objects[0].position, Position::new(5., 5.));
+    /// undo.apply(&mut level).unwrap();
+    /// assert_eq!(level.objects[0].position, Level::new().objects[0].position);
+    /// ```
+    pub fn apply(self, level: &mut Level) -> Result<Edit, EditError> {
+        Ok(match self {
+            Edit::InsertVertex {
+                polygon,
+                vertex,
+                position,
+            } => {
+                let vertices = polygon_vertices(level, polygon)?;
+                if vertex > vertices.len() {
+                    return Err(EditError::NoVertex { polygon, vertex });
+                }
+                vertices.insert(vertex, position);
+                Edit::RemoveVertex { polygon, vertex }
+            }
+            Edit::MoveVertex {
+                polygon,
+                vertex,
+                position,
+            } => {
+                let slot = polygon_vertices(level, polygon)?
+                    .get_mut(vertex)
+                    .ok_or(EditError::NoVertex { polygon, vertex })?;
+                Edit::MoveVertex {
+                    polygon,
+                    vertex,
+                    position: mem::replace(slot, position),
+                }
+            }
+            Edit::RemoveVertex { polygon, vertex } => {
+                let vertices = polygon_vertices(level, polygon)?;
+                if vertex >= vertices.len() {
+                    return Err(EditError::NoVertex { polygon, vertex });
+                }
+                Edit::InsertVertex {
+                    polygon,
+                    vertex,
+                    position: vertices.remove(vertex),
+                }
+            }
+            Edit::InsertPolygon { index, polygon } => {
+                if index > level.polygons.len() {
+                    return Err(EditError::NoPolygon(index));
+                }
+                level.polygons.insert(index, polygon);
+                Edit::RemovePolygon { index }
+            }
+            Edit::MovePolygon { index, offset } => {
+                for vertex in polygon_vertices(level, index)? {
+                    vertex.x += offset.x;
+                    vertex.y += offset.y;
+                }
+                Edit::MovePolygon {
+                    index,
+                    offset: Position::new(-offset.x, -offset.y),
+                }
+            }
+            Edit::RemovePolygon { index } => {
+                if index >= level.polygons.len() {
+                    return Err(EditError::NoPolygon(index));
+                }
+                Edit::InsertPolygon {
+                    index,
+                    polygon: level.polygons.remove(index),
+                }
+            }
+            Edit::InsertObject { index, object } => {
+                if index > level.objects.len() {
+                    return Err(EditError::NoObject(index));
+                }
+                level.objects.insert(index, object);
+                Edit::RemoveObject { index }
+            }
+            Edit::MoveObject { index, position } => {
+                let object = level
+                    .objects
+                    .get_mut(index)
+                    .ok_or(EditError::NoObject(index))?;
+                Edit::MoveObject {
+                    index,
+                    position: mem::replace(&mut object.position, position),
+                }
+            }
+            Edit::RemoveObject { index } => {
+                if index >= level.objects.len() {
+                    return Err(EditError::NoObject(index));
+                }
+                Edit::InsertObject {
+                    index,
+                    object: level.objects.remove(index),
+                }
+            }
+            Edit::SetObjectType { index, object_type } => {
+                let object = level
+                    .objects
+                    .get_mut(index)
+                    .ok_or(EditError::NoObject(index))?;
+                Edit::SetObjectType {
+                    index,
+                    object_type: mem::replace(&mut object.object_type, object_type),
+                }
+            }
+            Edit::SetGravity { index, gravity } => {
+                let object = level
+                    .objects
+                    .get_mut(index)
+                    .ok_or(EditError::NoObject(index))?;
+                match object.object_type {
+                    ObjectType::Apple {
+                        gravity: ref mut current,
+                        ..
+                    } => Edit::SetGravity {
+                        index,
+                        gravity: mem::replace(current, gravity),
+                    },
+                    _ => return Err(EditError::NotAnApple(index)),
+                }
+            }
+            Edit::InsertPicture { index, picture } => {
+                if index > level.pictures.len() {
+                    return Err(EditError::NoPicture(index));
+                }
+                level.pictures.insert(index, picture);
+                Edit::RemovePicture { index }
+            }
+            Edit::MovePicture { index, position } => {
+                let picture = level
+                    .pictures
+                    .get_mut(index)
+                    .ok_or(EditError::NoPicture(index))?;
+                Edit::MovePicture {
+                    index,
+                    position: mem::replace(&mut picture.position, position),
+                }
+            }
+            Edit::RemovePicture { index } => {
+                if index >= level.pictures.len() {
+                    return Err(EditError::NoPicture(index));
+                }
+                Edit::InsertPicture {
+                    index,
+                    picture: level.pictures.remove(index),
+                }
+            }
+            Edit::SetTitle(title) => Edit::SetTitle(mem::replace(&mut level.title, title)),
+            Edit::SetLgr(lgr) => Edit::SetLgr(mem::replace(&mut level.lgr, lgr)),
+            Edit::SetGround(ground) => Edit::SetGround(mem::replace(&mut level.ground, ground)),
+            Edit::SetSky(sky) => Edit::SetSky(mem::replace(&mut level.sky, sky)),
+            Edit::Group(edits) => {
+                let mut inverses = Vec::with_capacity(edits.len());
+                for edit in edits {
+                    match edit.apply(level) {
+                        Ok(inverse) => inverses.push(inverse),
+                        Err(error) => {
+                            // Put the level back the way it was.
+                            for inverse in inverses.into_iter().rev() {
+                                inverse
+                                    .apply(level)
+                                    .expect("inverse of an applied edit is valid");
+                            }
+                            return Err(error);
+                        }
+                    }
+                }
+                inverses.reverse();
+                Edit::Group(inverses)
+            }
+        })
+    }
+}
+
+fn polygon_vertices(
+    level: &mut Level,
+    polygon: usize,
+) -> Result<&mut Vec<Position<f64>>, EditError> {
+    level
+        .polygons
+        .get_mut(polygon)
+        .map(|p| &mut p.vertices)
+        .ok_or(EditError::NoPolygon(polygon))
+}
+
+/// Undo and redo history of edits made to a level.
+///
+/// Edits made between `begin_group` and `end_group` are undone and redone together. Groups
+/// can be nested.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::edit::*;
+/// # use elma::lev::*;
+/// let mut level = Level::new();
+/// let mut history = History::new();
+/// history.begin_group();
+/// history.apply(&mut level, Edit::SetTitle("Hills".into())).unwrap();
+/// history.apply(&mut level, Edit::RemoveObject { index: 1 }).unwrap();
+/// history.end_group();
+///
+/// assert!(history.undo(&mut level).unwrap());
+/// assert_eq!(level.objects.len(), 2);
+/// assert!(history.redo(&mut level).unwrap());
+/// assert_eq!(level.title, "Hills");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    groups: Vec<Vec<Edit>>,
+}
+
+impl History {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies an edit to the level and records it, clearing the redo list.
+    pub fn apply(&mut self, level: &mut Level, edit: Edit) -> Result<(), EditError> {
+        let inverse = edit.apply(level)?;
+        match self.groups.last_mut() {
+            Some(group) => group.push(inverse),
+            None => self.undo.push(inverse),
+        }
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Starts a group of edits that are undone as one.
+    pub fn begin_group(&mut self) {
+        self.groups.push(vec![]);
+    }
+
+    /// Ends the innermost group. Empty groups are not recorded.
+    pub fn end_group(&mut self) {
+        if let Some(mut group) = self.groups.pop() {
+            if group.is_empty() {
+                return;
+            }
+            group.reverse();
+            match self.groups.last_mut() {
+                Some(parent) => parent.push(Edit::Group(group)),
+                None => self.undo.push(Edit::Group(group)),
+            }
+        }
+    }
+
+    /// Reverts the last edit or group, ending any open groups first. Returns `false` when there
+    /// is nothing to undo.
+    pub fn undo(&mut self, level: &mut Level) -> Result<bool, EditError> {
+        while !self.groups.is_empty() {
+            self.end_group();
+        }
+        step(level, &mut self.undo, &mut self.redo)
+    }
+
+    /// Applies the last undone edit or group again. Returns `false` when there is nothing to
+    /// redo.
+    pub fn redo(&mut self, level: &mut Level) -> Result<bool, EditError> {
+        step(level, &mut self.redo, &mut self.undo)
+    }
+
+    /// Whether there is anything to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.groups.iter().any(|group| !group.is_empty())
+    }
+
+    /// Whether there is anything to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all recorded edits.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.groups.clear();
+    }
+}
+
+// Applies the last edit of `from` and records its inverse in `to`. A failing edit stays in
+// `from`.
+fn step(level: &mut Level, from: &mut Vec<Edit>, to: &mut Vec<Edit>) -> Result<bool, EditError> {
+    let edit = match from.pop() {
+        Some(edit) => edit,
+        None => return Ok(false),
+    };
+    match edit.clone().apply(level) {
+        Ok(inverse) => {
+            to.push(inverse);
+            Ok(true)
+        }
+        Err(error) => {
+            from.push(edit);
+            Err(error)
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use super::{lev::Polygon, Position};
+use std::collections::{HashMap, HashSet};
+
+// Points closer than this (in level units) are treated as the same vertex.
+pub(crate) const SNAP: f64 = 1e-9;
+// Distance from a boundary segment at which the regions on either side are sampled.
+const PROBE: f64 = 1e-7;
+
+/// Boolean operation to combine two polygon sets with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Region covered by either set.
+    Union,
+    /// Region covered by both sets.
+    Intersection,
+    /// Region covered by the subject set but not by the clip set.
+    Difference,
+    /// Region covered by exactly one of the sets.
+    Xor,
+}
+
+impl BooleanOp {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+type Key = (i64, i64);
+
+// Piece of a split edge.
+struct SubEdge {
+    from: Position<f64>,
+    to: Position<f64>,
+    key: (Key, Key),
+}
+
+/// Combines two sets of polygons with a boolean operation.
+///
+/// Both sets are interpreted using the even-odd rule, the same way Elma decides what is ground
+/// and what is air: inside an odd number of polygons is air, the inside of the level. Grass
+/// polygons are ignored. The returned polygons do not cross each other and their even-odd
+/// nesting describes the resulting air region, so a union with a tunnel carves it out of the
+/// ground, while a difference fills the clip region with ground.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::geometry::*;
+/// # use elma::lev::Polygon;
+/// # use elma::Position;
+/// let square = |x: f64, y: f64| Polygon {
+///     grass: false,
+///     vertices: vec![
+///         Position::new(x, y),
+///         Position::new(x + 2., y),
+///         Position::new(x + 2., y + 2.),
+///         Position::new(x, y + 2.),
+///     ],
+/// };
+/// let merged = boolean(&[square(0., 0.)], &[square(1., 1.)], BooleanOp::Union);
+/// assert_eq!(merged.len(), 1);
+/// assert_eq!(merged[0].vertices.len(), 8);
+/// ```
+pub fn boolean(subject: &[Polygon], clip: &[Polygon], op: BooleanOp) -> Vec<Polygon> {
+    let mut edges = vec![];
+    for (set, polygons) in [subject, clip].iter().enumerate() {
+        for polygon in polygons.iter().filter(|p| !p.grass && p.vertices.len() > 2) {
+            let n = polygon.vertices.len();
+            for i in 0..n {
+                let from = polygon.vertices[i];
+                let to = polygon.vertices[(i + 1) % n];
+                if key(from) != key(to) {
+                    edges.push((from, to, set));
+                }
+            }
+        }
+    }
+
+    // Split every edge where it meets another one and merge coinciding pieces. Each remaining
+    // segment lies entirely inside or outside of the result, or on its boundary.
+    let mut segments = vec![];
+    let mut seen = HashSet::new();
+    for edge in split_edges(&edges) {
+        if seen.insert(edge.key) {
+            segments.push(edge);
+        }
+    }
+
+    // Keep the segments separating the resulting region from the rest, oriented so that the
+    // region lies to their left.
+    let mut boundary = vec![];
+    for segment in &segments {
+        let length = distance(&segment.from, &segment.to);
+        let (nx, ny) = (
+            -(segment.to.y - segment.from.y) / length * PROBE,
+            (segment.to.x - segment.from.x) / length * PROBE,
+        );
+        let mid_x = (segment.from.x + segment.to.x) / 2.;
+        let mid_y = (segment.from.y + segment.to.y) / 2.;
+        let left = Position::new(mid_x + nx, mid_y + ny);
+        let right = Position::new(mid_x - nx, mid_y - ny);
+
+        let left = op.apply(parity(&edges, 0, &left), parity(&edges, 1, &left));
+        let right = op.apply(parity(&edges, 0, &right), parity(&edges, 1, &right));
+        if left && !right {
+            boundary.push((segment.from, segment.to));
+        } else if right && !left {
+            boundary.push((segment.to, segment.from));
+        }
+    }
+
+    chain(&boundary)
+        .into_iter()
+        .map(simplify_ring)
+        .filter(|ring| ring.len() > 2 && area(ring).abs() > SNAP)
+        .map(|vertices| Polygon {
+            grass: false,
+            vertices,
+        })
+        .collect()
+}
+
+/// Union of two polygon sets. See `boolean`.
+pub fn union(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOp::Union)
+}
+
+/// Intersection of two polygon sets. See `boolean`.
+pub fn intersection(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOp::Intersection)
+}
+
+/// Difference of two polygon sets: the air of `subject` outside of `clip`, so the area of `clip`
+/// becomes ground. See `boolean`.
+pub fn difference(subject: &[Polygon], clip: &[Polygon]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOp::Difference)
+}
+
+/// Signed area of a closed polyline; positive when the vertices go counter-clockwise.
+pub fn area(vertices: &[Position<f64>]) -> f64 {
+    let n = vertices.len();
+    let mut sum = 0.;
+    for i in 0..n {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % n];
+        sum += a.x * b.y - b.x * a.y;
+    }
+    sum / 2.
+}
+
+/// Returns whether a point is in the air of the polygons, inside an odd number of them. Grass
+/// polygons are ignored.
+pub fn contains(polygons: &[Polygon], point: &Position<f64>) -> bool {
+    let mut inside = false;
+    for polygon in polygons.iter().filter(|p| !p.grass) {
+        let n = polygon.vertices.len();
+        for i in 0..n {
+            if crosses_ray(&polygon.vertices[i], &polygon.vertices[(i + 1) % n], point) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// Whether edge a-b crosses the horizontal ray going right from `point`.
+pub(crate) fn crosses_ray(a: &Position<f64>, b: &Position<f64>, point: &Position<f64>) -> bool {
+    if (a.y > point.y) != (b.y > point.y) {
+        let x = a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        x > point.x
+    } else {
+        false
+    }
+}
+
+pub(crate) fn cross(ax: f64, ay: f64, bx: f64, by: f64) -> f64 {
+    ax * by - ay * bx
+}
+
+pub(crate) fn distance(a: &Position<f64>, b: &Position<f64>) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn key(p: Position<f64>) -> Key {
+    ((p.x / SNAP).round() as i64, (p.y / SNAP).round() as i64)
+}
+
+fn undirected_key(a: Key, b: Key) -> (Key, Key) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+// Even-odd parity of `set` at `point`.
+fn parity(
+    edges: &[(Position<f64>, Position<f64>, usize)],
+    set: usize,
+    point: &Position<f64>,
+) -> bool {
+    edges
+        .iter()
+        .filter(|e| e.2 == set)
+        .fold(false, |inside, e| inside != crosses_ray(&e.0, &e.1, point))
+}
+
+// Splits every edge at the points where it touches or crosses other edges.
+fn split_edges(edges: &[(Position<f64>, Position<f64>, usize)]) -> Vec<SubEdge> {
+    let mut cuts: Vec<Vec<(f64, Position<f64>)>> = edges
+        .iter()
+        .map(|&(from, to, _)| vec![(0., from), (1., to)])
+        .collect();
+
+    for i in 0..edges.len() {
+        for j in i + 1..edges.len() {
+            let (p, p2, _) = edges[i];
+            let (q, q2, _) = edges[j];
+            if p.x.max(p2.x) + SNAP < q.x.min(q2.x)
+                || q.x.max(q2.x) + SNAP < p.x.min(p2.x)
+                || p.y.max(p2.y) + SNAP < q.y.min(q2.y)
+                || q.y.max(q2.y) + SNAP < p.y.min(p2.y)
+            {
+                continue;
+            }
+
+            let (rx, ry) = (p2.x - p.x, p2.y - p.y);
+            let (sx, sy) = (q2.x - q.x, q2.y - q.y);
+            let (qpx, qpy) = (q.x - p.x, q.y - p.y);
+            let r_len = rx.hypot(ry);
+            let s_len = sx.hypot(sy);
+            let denom = cross(rx, ry, sx, sy);
+
+            if denom.abs() > 1e-12 * r_len * s_len {
+                let t = cross(qpx, qpy, sx, sy) / denom;
+                let u = cross(qpx, qpy, rx, ry) / denom;
+                let t_eps = SNAP / r_len;
+                let u_eps = SNAP / s_len;
+                if t < -t_eps || t > 1. + t_eps || u < -u_eps || u > 1. + u_eps {
+                    continue;
+                }
+                // Prefer existing vertices so shared points stay bit-identical.
+                let point = if u.abs() <= u_eps {
+                    q
+                } else if (u - 1.).abs() <= u_eps {
+                    q2
+                } else if t.abs() <= t_eps {
+                    p
+                } else if (t - 1.).abs() <= t_eps {
+                    p2
+                } else {
+                    Position::new(p.x + t * rx, p.y + t * ry)
+                };
+                cuts[i].push((t.clamp(0., 1.), point));
+                cuts[j].push((u.clamp(0., 1.), point));
+            } else if cross(qpx, qpy, rx, ry).abs() <= SNAP * r_len {
+                // Collinear, so split each edge at the other's endpoints.
+                for point in &[q, q2] {
+                    let t = ((point.x - p.x) * rx + (point.y - p.y) * ry) / (r_len * r_len);
+                    if t > 0. && t < 1. {
+                        cuts[i].push((t, *point));
+                    }
+                }
+                for point in &[p, p2] {
+                    let u = ((point.x - q.x) * sx + (point.y - q.y) * sy) / (s_len * s_len);
+                    if u > 0. && u < 1. {
+                        cuts[j].push((u, *point));
+                    }
+                }
+            }
+        }
+    }
+
+    // Canonical position for every vertex key, so pieces meet exactly.
+    let mut canonical: HashMap<Key, Position<f64>> = HashMap::new();
+    let mut sub_edges = vec![];
+    for mut points in cuts {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let points: Vec<_> = points
+            .into_iter()
+            .map(|(_, p)| *canonical.entry(key(p)).or_insert(p))
+            .collect();
+        for pair in points.windows(2) {
+            let (from_key, to_key) = (key(pair[0]), key(pair[1]));
+            if from_key != to_key {
+                sub_edges.push(SubEdge {
+                    from: pair[0],
+                    to: pair[1],
+                    key: undirected_key(from_key, to_key),
+                });
+            }
+        }
+    }
+    sub_edges
+}
+
+// Links directed boundary edges into closed rings. Where several rings meet at a vertex, the
+// sharpest left turn is taken so rings touch without crossing.
+fn chain(edges: &[(Position<f64>, Position<f64>)]) -> Vec<Vec<Position<f64>>> {
+    let mut outgoing: HashMap<Key, Vec<usize>> = HashMap::new();
+    for (n, edge) in edges.iter().enumerate() {
+        outgoing.entry(key(edge.0)).or_default().push(n);
+    }
+    let none = vec![];
+
+    let mut used = vec![false; edges.len()];
+    let mut rings = vec![];
+    for start in 0..edges.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut ring = vec![edges[start].0];
+        let mut current = start;
+        loop {
+            let (from, to) = edges[current];
+            let (in_x, in_y) = (to.x - from.x, to.y - from.y);
+            let mut best: Option<(f64, usize)> = None;
+            for &candidate in outgoing.get(&key(to)).unwrap_or(&none) {
+                if used[candidate] && candidate != start {
+                    continue;
+                }
+                let (out_x, out_y) = (
+                    edges[candidate].1.x - edges[candidate].0.x,
+                    edges[candidate].1.y - edges[candidate].0.y,
+                );
+                let turn = cross(in_x, in_y, out_x, out_y).atan2(in_x * out_x + in_y * out_y);
+                if best.is_none_or(|(best_turn, _)| turn > best_turn) {
+                    best = Some((turn, candidate));
+                }
+            }
+            match best {
+                Some((_, next)) if next == start => {
+                    rings.push(ring);
+                    break;
+                }
+                Some((_, next)) => {
+                    used[next] = true;
+                    ring.push(edges[next].0);
+                    current = next;
+                }
+                None => break,
+            }
+        }
+    }
+    rings
+}
+
+// Removes vertices lying on the straight line between their neighbours.
+pub(crate) fn simplify_ring(mut ring: Vec<Position<f64>>) -> Vec<Position<f64>> {
+    let mut changed = true;
+    while changed && ring.len() > 2 {
+        changed = false;
+        let mut n = 0;
+        while ring.len() > 2 && n < ring.len() {
+            let len = ring.len();
+            let prev = ring[(n + len - 1) % len];
+            let current = ring[n];
+            let next = ring[(n + 1) % len];
+            let offset = cross(
+                current.x - prev.x,
+                current.y - prev.y,
+                next.x - prev.x,
+                next.y - prev.y,
+            );
+            let forward = (current.x - prev.x) * (next.x - current.x)
+                + (current.y - prev.y) * (next.y - current.y);
+            if offset.abs() <= SNAP * distance(&prev, &next) && forward >= 0. {
+                ring.remove(n);
+                changed = true;
+            } else {
+                n += 1;
+            }
+        }
+    }
+    ring
+}
@@ -0,0 +1,146 @@
+use super::{
+    geometry::{contains, distance},
+    lev::{Level, Polygon},
+    Position,
+};
+use std::f64::consts::PI;
+
+// Vertical distance at which air and ground are sampled around an edge.
+const PROBE: f64 = 1e-6;
+
+/// Options for `generate_grass`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrassOptions {
+    /// Steepest ground edge that still gets grass, in radians from horizontal.
+    pub max_slope: f64,
+    /// How far the grass polygon reaches below the ground surface, in level units.
+    pub thickness: f64,
+}
+
+impl Default for GrassOptions {
+    fn default() -> Self {
+        GrassOptions {
+            max_slope: PI / 3.,
+            thickness: 0.5,
+        }
+    }
+}
+
+impl GrassOptions {
+    /// Creates new grass options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Generates grass polygons along the upward-facing edges of the level's ground polygons.
+///
+/// Every run of connected ground edges with air above them and a slope of at most
+/// `max_slope` gets one grass polygon. The polygon follows the run from left to right and is
+/// closed below the surface, with the closing bottom edge as its longest edge since Elma does
+/// not draw grass along the longest edge. Existing grass polygons are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::grass::*;
+/// # use elma::lev::*;
+/// let mut level = Level::new();
+/// let grass = generate_grass(&level, &GrassOptions::new());
+/// assert_eq!(grass.len(), 1);
+/// level.polygons.extend(grass);
+/// ```
+pub fn generate_grass(level: &Level, options: &GrassOptions) -> Vec<Polygon> {
+    let mut grass = vec![];
+    for polygon in level.polygons.iter().filter(|p| !p.grass) {
+        for run in surface_runs(level, polygon, options.max_slope) {
+            grass.push(grass_polygon(run, options.thickness));
+        }
+    }
+    grass
+}
+
+// Runs of consecutive upward-facing edges of `polygon`, as vertex lists going left to right.
+fn surface_runs(level: &Level, polygon: &Polygon, max_slope: f64) -> Vec<Vec<Position<f64>>> {
+    let vertices = &polygon.vertices;
+    let n = vertices.len();
+    let surface: Vec<bool> = (0..n)
+        .map(|i| is_surface(level, &vertices[i], &vertices[(i + 1) % n], max_slope))
+        .collect();
+
+    // Start at an edge that begins a run, so runs wrapping around the end stay in one piece.
+    let start = match (0..n).find(|&i| surface[i] && !surface[(i + n - 1) % n]) {
+        Some(start) => start,
+        None => return vec![],
+    };
+
+    let mut runs = vec![];
+    let mut run: Vec<Position<f64>> = vec![];
+    for offset in 0..n {
+        let i = (start + offset) % n;
+        if surface[i] {
+            if run.is_empty() {
+                run.push(vertices[i]);
+            }
+            run.push(vertices[(i + 1) % n]);
+        } else if !run.is_empty() {
+            runs.push(run);
+            run = vec![];
+        }
+    }
+    if !run.is_empty() {
+        runs.push(run);
+    }
+
+    for run in &mut runs {
+        if run[0].x > run[run.len() - 1].x {
+            run.reverse();
+        }
+    }
+    runs
+}
+
+fn is_surface(level: &Level, a: &Position<f64>, b: &Position<f64>, max_slope: f64) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    if dx == 0. || (dy / dx).atan().abs() > max_slope {
+        return false;
+    }
+    let mid = Position::new((a.x + b.x) / 2., (a.y + b.y) / 2.);
+    // Inside an odd number of polygons is air.
+    let air_above = contains(&level.polygons, &Position::new(mid.x, mid.y + PROBE));
+    let air_below = contains(&level.polygons, &Position::new(mid.x, mid.y - PROBE));
+    air_above && !air_below
+}
+
+// Closes a surface run below the ground, widening the bottom edge until it is the longest.
+fn grass_polygon(run: Vec<Position<f64>>, thickness: f64) -> Polygon {
+    let first = run[0];
+    let last = run[run.len() - 1];
+    let bottom = run.iter().fold(first.y, |min, p| min.min(p.y)) - thickness;
+    let width = last.x - first.x;
+    let longest_top = run
+        .windows(2)
+        .map(|pair| distance(&pair[0], &pair[1]))
+        .fold(0., f64::max);
+
+    let mut extend = 0.;
+    for _ in 0..16 {
+        let left = Position::new(first.x - extend, bottom);
+        let right = Position::new(last.x + extend, bottom);
+        let longest_other = longest_top
+            .max(distance(&first, &left))
+            .max(distance(&last, &right));
+        if width + 2. * extend > longest_other {
+            break;
+        }
+        extend = (longest_other * 1.01 - width) / 2.;
+    }
+
+    let mut vertices = run;
+    vertices.push(Position::new(last.x + extend, bottom));
+    vertices.push(Position::new(first.x - extend, bottom));
+    Polygon {
+        grass: true,
+        vertices,
+    }
+}
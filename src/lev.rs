@@ -1,11 +1,14 @@
 use super::{
-    constants::{OBJECT_RADIUS, PLAYER_TOP10_SIZE, TOP10_SIZE},
-    utils::{parse_top10, string_null_pad, trim_string, write_top10},
+    constants::{
+        MAX_OBJECTS, MAX_PICTURES, MAX_POLYGONS, OBJECT_RADIUS, PLAYER_TOP10_SIZE, TOP10_SIZE,
+    },
+    utils::{parse_top10, string_null_pad, trim_string, write_top10, ByteReader, StreamReader},
     BestTimes, Clip, ElmaError, Position, Version,
 };
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use rand::random;
 use std::fs;
+use std::io::{BufReader, Read, Write};
 use std::path::PathBuf;
 
 // Magic arbitrary number signifying end-of-data in level file.
@@ -34,6 +37,39 @@ pub enum TopologyError {
     TooHigh(f64),
 }
 
+/// Level field that cannot be written to a level file, as reported by `Level::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// Path of the field, such as `title` or `pictures[2].mask`.
+    pub field: String,
+    /// Constraint the field breaks.
+    pub kind: FieldErrorKind,
+}
+
+/// Constraints on level fields.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldErrorKind {
+    /// String does not fit its fixed-size field along with a null terminator, with its length
+    /// and the maximum length.
+    TooLong {
+        /// Length of the string in bytes.
+        length: usize,
+        /// Maximum length in bytes.
+        max: usize,
+    },
+    /// String contains non-ASCII characters.
+    NonASCII,
+    /// Number is outside the allowed range, with its value and the inclusive limits.
+    OutOfRange {
+        /// Value of the field.
+        value: i32,
+        /// Smallest allowed value.
+        min: i32,
+        /// Largest allowed value.
+        max: i32,
+    },
+}
+
 /// This trait specifies something having a rectangle bounding box.
 pub trait BoundingBox {
     /// Bounding box of `&self`, going from top-left, top-right, bottom-left to bottom-right.
@@ -50,7 +86,7 @@ pub enum Top10Save {
 }
 
 /// Type of object.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ObjectType {
     /// Apple.
     Apple {
@@ -98,7 +134,7 @@ impl Default for GravityDirection {
 }
 
 /// Object struct. Every level requires one `ObjectType::Player` Object and at least one `ObjectType::Exit` Object.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct Object {
     /// Position. See `Position` struct.
     pub position: Position<f64>,
@@ -130,7 +166,7 @@ impl Object {
 }
 
 /// Polygon struct.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct Polygon {
     /// Grass polygon.
     pub grass: bool,
@@ -179,15 +215,36 @@ impl Polygon {
     }
 }
 
+/// What a picture shows.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PictureKind {
+    /// Picture from the LGR.
+    Normal {
+        /// Picture name.
+        name: String,
+    },
+    /// Texture filling the shape of a mask.
+    Texture {
+        /// Texture name.
+        texture: String,
+        /// Mask name.
+        mask: String,
+    },
+}
+
+impl Default for PictureKind {
+    fn default() -> PictureKind {
+        PictureKind::Normal {
+            name: String::new(),
+        }
+    }
+}
+
 /// Picture struct.
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct Picture {
-    /// Picture name.
-    pub name: String,
-    /// Texture name.
-    pub texture: String,
-    /// Mask name.
-    pub mask: String,
+    /// Picture, or texture and mask.
+    pub kind: PictureKind,
     /// Position. See `Position` struct.
     pub position: Position<f64>,
     /// Z-distance
@@ -200,13 +257,127 @@ impl Picture {
     /// Creates a new picture with default values.
     pub fn new() -> Self {
         Picture {
-            name: "barrel".into(),
+            kind: PictureKind::Normal {
+                name: "barrel".into(),
+            },
             distance: 600,
             ..Default::default()
         }
     }
 }
 
+/// Level header and section counts, read without parsing the level geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelHeader {
+    /// Elma or Across level.
+    pub version: Version,
+    /// Random number that links level file to replay files.
+    pub link: u32,
+    /// Contains four integrity checks.
+    pub integrity: [f64; 4],
+    /// Level title.
+    pub title: String,
+    /// LGR file name.
+    pub lgr: String,
+    /// Ground texture name.
+    pub ground: String,
+    /// Sky texture name.
+    pub sky: String,
+    /// Number of polygons, including grass polygons.
+    pub polygon_count: usize,
+    /// Number of objects.
+    pub object_count: usize,
+    /// Number of pictures.
+    pub picture_count: usize,
+}
+
+impl LevelHeader {
+    /// Reads the header of a level file. Polygons, objects and pictures are skipped over
+    /// without being parsed, and the top10 lists are not read at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// let header = LevelHeader::load("tests/assets/levels/test_1.lev").unwrap();
+    /// assert_eq!(header.title, "Rust test");
+    /// ```
+    pub fn load<P: Into<PathBuf>>(path: P) -> Result<Self, ElmaError> {
+        let file = fs::File::open(path.into())?;
+        LevelHeader::read_from(BufReader::new(file))
+    }
+
+    /// Reads the header of a level from bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use elma::lev::*;
+    /// let header = LevelHeader::from_bytes(&[0,1,2]).unwrap();
+    /// ```
+    pub fn from_bytes<B: AsRef<[u8]>>(buffer: B) -> Result<Self, ElmaError> {
+        LevelHeader::read_from(buffer.as_ref())
+    }
+
+    /// Reads the header of a level from a reader, consuming it up to the picture count.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, ElmaError> {
+        let mut reader = StreamReader::new(reader);
+        // Version.
+        let version = match &reader.take(5, "header")?[..] {
+            b"POT14" => Version::Elma,
+            b"POT06" => return Err(ElmaError::AcrossUnsupported),
+            _ => return Err(ElmaError::InvalidLevelFile),
+        };
+
+        // Link, after the unused lower short of it.
+        reader.skip_records(1, 2, "header")?;
+        let link = reader.read_u32("header")?;
+
+        // Integrity checksums.
+        let mut integrity = [0f64; 4];
+        for sum in &mut integrity {
+            *sum = reader.read_f64("header")?;
+        }
+
+        // Level, LGR, ground and sky names.
+        let title = reader.read_string(51, "header")?;
+        let lgr = reader.read_string(16, "header")?;
+        let ground = reader.read_string(10, "header")?;
+        let sky = reader.read_string(10, "header")?;
+
+        // Polygons, skipping over each polygon's vertices.
+        let polygon_count = (reader.read_f64("polygons")? - 0.464_364_3).round() as usize;
+        for _ in 0..polygon_count {
+            reader.skip_records(1, 4, "polygons")?;
+            let vertex_count = reader.read_i32("polygons")?;
+            if vertex_count < 0 {
+                return Err(ElmaError::InvalidLevelFile);
+            }
+            reader.skip_records(vertex_count as usize, 16, "polygons")?;
+        }
+
+        // Objects.
+        let object_count = (reader.read_f64("objects")? - 0.464_364_3).round() as usize;
+        reader.skip_records(object_count, 28, "objects")?;
+
+        // Pictures.
+        let picture_count = (reader.read_f64("pictures")? - 0.234_567_2).round() as usize;
+
+        Ok(LevelHeader {
+            version,
+            link,
+            integrity,
+            title,
+            lgr,
+            ground,
+            sky,
+            polygon_count,
+            object_count,
+            picture_count,
+        })
+    }
+}
+
 /// Level struct that contains all level information.
 #[derive(Debug, PartialEq)]
 pub struct Level {
@@ -335,6 +506,34 @@ impl Level {
         Ok(lev)
     }
 
+    /// Reads only the header and section counts of a level file. See `LevelHeader`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// let header = Level::peek("tests/assets/levels/test_1.lev").unwrap();
+    /// assert_eq!(header.polygon_count, 2);
+    /// ```
+    pub fn peek<P: Into<PathBuf>>(path: P) -> Result<LevelHeader, ElmaError> {
+        LevelHeader::load(path)
+    }
+
+    /// Reads a level from a reader.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// let file = std::fs::File::open("tests/assets/levels/test_1.lev").unwrap();
+    /// let level = Level::read_from(file).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ElmaError> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        Level::parse_level(&buffer)
+    }
+
     /// Load a level from bytes.
     ///
     /// # Examples
@@ -350,60 +549,54 @@ impl Level {
     /// Parses the raw binary data into `Level` struct fields.
     fn parse_level(buffer: &[u8]) -> Result<Self, ElmaError> {
         let mut level = Level::new();
+        let mut reader = ByteReader::new(buffer);
         // Version.
-        let (version, remaining) = buffer.split_at(5);
-        level.version = match version {
+        level.version = match reader.take(5, "header")? {
             b"POT14" => Version::Elma,
             b"POT06" => return Err(ElmaError::AcrossUnsupported),
             _ => return Err(ElmaError::InvalidLevelFile),
         };
 
         // Link.
-        let (_, mut remaining) = remaining.split_at(2); // Never used
-        level.link = remaining.read_u32::<LE>()?;
+        reader.take(2, "header")?; // Never used
+        level.link = reader.read_u32("header")?;
 
         // Integrity checksums.
         for i in 0..4 {
-            level.integrity[i] = remaining.read_f64::<LE>()?;
+            level.integrity[i] = reader.read_f64("header")?;
         }
 
         // Level name.
-        let (name, remaining) = remaining.split_at(51);
-        level.title = trim_string(name)?;
+        level.title = reader.read_string(51, "header")?;
         // LGR name.
-        let (lgr, remaining) = remaining.split_at(16);
-        level.lgr = trim_string(lgr)?;
+        level.lgr = reader.read_string(16, "header")?;
         // Ground texture name.
-        let (ground, remaining) = remaining.split_at(10);
-        level.ground = trim_string(ground)?;
+        level.ground = reader.read_string(10, "header")?;
         // Sky texture name.
-        let (sky, mut remaining) = remaining.split_at(10);
-        level.sky = trim_string(sky)?;
+        level.sky = reader.read_string(10, "header")?;
 
         // Polygons.
-        let poly_count = (remaining.read_f64::<LE>()? - 0.464_364_3).round() as usize;
-        let (polygons, read_bytes) = Level::parse_polygons(remaining, poly_count)?;
-        level.polygons = polygons;
-        let (_, mut remaining) = remaining.split_at(read_bytes);
+        let poly_count = (reader.read_f64("polygons")? - 0.464_364_3).round() as usize;
+        level.polygons = Level::parse_polygons(&mut reader, poly_count)?;
 
         // Objects.
-        let object_count = (remaining.read_f64::<LE>()? - 0.464_364_3).round() as usize;
-        let (object_data, mut remaining) = remaining.split_at(object_count * 28);
+        let object_count = (reader.read_f64("objects")? - 0.464_364_3).round() as usize;
+        let object_data = reader.take_records(object_count, 28, "objects")?;
         level.objects = Level::parse_objects(object_data, object_count)?;
 
         // Pictures.
-        let picture_count = (remaining.read_f64::<LE>()? - 0.234_567_2).round() as usize;
-        let (picture_data, mut remaining) = remaining.split_at(picture_count * 54);
+        let picture_count = (reader.read_f64("pictures")? - 0.234_567_2).round() as usize;
+        let picture_data = reader.take_records(picture_count, 54, "pictures")?;
         level.pictures = Level::parse_pictures(picture_data, picture_count)?;
 
         // EOD marker expected at this point.
-        let expected = remaining.read_i32::<LE>()?;
+        let expected = reader.read_i32("end of data")?;
         if expected != EOD {
             return Err(ElmaError::EODMismatch);
         }
 
         // First decrypt the top10 blocks.
-        let (top10, mut remaining) = remaining.split_at(TOP10_SIZE);
+        let top10 = reader.take(TOP10_SIZE, "top10")?;
         let decrypted_top10_data = crypt_top10(top10);
 
         // Single-player list.
@@ -415,7 +608,7 @@ impl Level {
         level.best_times.multi = parse_top10(multi)?;
 
         // EOF marker expected at this point.
-        let expected = remaining.read_i32::<LE>()?;
+        let expected = reader.read_i32("end of file")?;
         if expected != EOF {
             return Err(ElmaError::EOFMismatch);
         }
@@ -423,23 +616,23 @@ impl Level {
         Ok(level)
     }
 
-    fn parse_polygons(mut buffer: &[u8], n: usize) -> Result<(Vec<Polygon>, usize), ElmaError> {
+    fn parse_polygons(reader: &mut ByteReader, n: usize) -> Result<Vec<Polygon>, ElmaError> {
         let mut polygons = vec![];
-        let mut read_bytes = 0;
         for _ in 0..n {
-            read_bytes += 8;
-            let grass = buffer.read_i32::<LE>()? > 0;
-            let vertex_count = buffer.read_i32::<LE>()?;
+            let grass = reader.read_i32("polygons")? > 0;
+            let vertex_count = reader.read_i32("polygons")?;
+            if vertex_count < 0 {
+                return Err(ElmaError::InvalidLevelFile);
+            }
             let mut vertices: Vec<Position<f64>> = vec![];
             for _ in 0..vertex_count {
-                read_bytes += 16;
-                let x = buffer.read_f64::<LE>()?;
-                let y = buffer.read_f64::<LE>()?;
+                let x = reader.read_f64("polygons")?;
+                let y = reader.read_f64("polygons")?;
                 vertices.push(Position::new(x, -y));
             }
             polygons.push(Polygon { grass, vertices });
         }
-        Ok((polygons, read_bytes))
+        Ok(polygons)
     }
 
     fn parse_objects(mut buffer: &[u8], n: usize) -> Result<Vec<Object>, ElmaError> {
@@ -478,13 +671,10 @@ impl Level {
     fn parse_pictures(mut buffer: &[u8], n: usize) -> Result<Vec<Picture>, ElmaError> {
         let mut pictures = vec![];
         for _ in 0..n {
-            let (name, temp_remaining) = buffer.split_at(10);
-            let name = trim_string(name)?;
-            let (texture, temp_remaining) = temp_remaining.split_at(10);
-            let texture = trim_string(texture)?;
-            let (mask, temp_remaining) = temp_remaining.split_at(10);
-            let mask = trim_string(mask)?;
-            buffer = temp_remaining;
+            let name = trim_string(&buffer[..10])?;
+            let texture = trim_string(&buffer[10..20])?;
+            let mask = trim_string(&buffer[20..30])?;
+            buffer = &buffer[30..];
             let x = buffer.read_f64::<LE>()?;
             let y = buffer.read_f64::<LE>()?;
             let distance = buffer.read_i32::<LE>()?;
@@ -496,10 +686,14 @@ impl Level {
                 other => return Err(ElmaError::InvalidClipping(other)),
             };
 
+            // A name makes it a normal picture, and the texture and mask are not used.
+            let kind = if name.is_empty() {
+                PictureKind::Texture { texture, mask }
+            } else {
+                PictureKind::Normal { name }
+            };
             pictures.push(Picture {
-                name,
-                texture,
-                mask,
+                kind,
                 position: Position::new(x, -y),
                 distance,
                 clip,
@@ -523,40 +717,65 @@ impl Level {
     /// ```
     pub fn to_bytes(&self, top_10: Top10Save) -> Result<Vec<u8>, ElmaError> {
         let mut buffer = vec![];
+        self.write_to(&mut buffer, top_10)?;
+        Ok(buffer)
+    }
 
+    /// Writes the level in its binary form to a writer.
+    ///
+    /// The level is written piece by piece as it is encoded, in many small writes, so wrap
+    /// unbuffered writers such as files in a `BufWriter`. A field that cannot be encoded, such as
+    /// a too long picture name, fails only once the data before it has been written; check the
+    /// level with `validate` first, or use `to_bytes` to encode all of it before writing.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Where to write the level to.
+    /// * `top10` - Specifies whether to keep the top10 list (true), or write an empty list (false).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// let level = Level::new();
+    /// let mut buffer = vec![];
+    /// level.write_to(&mut buffer, Top10Save::No).unwrap();
+    /// assert_eq!(Level::from_bytes(&buffer).unwrap().link, level.link);
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W, top_10: Top10Save) -> Result<(), ElmaError> {
         // Level version.
         match self.version {
-            Version::Elma => buffer.extend_from_slice(&[80, 79, 84, 49, 52]),
+            Version::Elma => writer.write_all(&[80, 79, 84, 49, 52])?,
             Version::Across => return Err(ElmaError::AcrossUnsupported),
         };
 
         // Lower short of link.
-        buffer.write_i16::<LE>((self.link & 0xFFFF) as i16)?;
+        writer.write_i16::<LE>((self.link & 0xFFFF) as i16)?;
         // Link.
-        buffer.write_u32::<LE>(self.link)?;
+        writer.write_u32::<LE>(self.link)?;
         // Integrity checksums.
         for sum in &self.calculate_integrity_sums(true) {
-            buffer.write_f64::<LE>(*sum)?;
+            writer.write_f64::<LE>(*sum)?;
         }
 
         // Level name.
-        buffer.extend_from_slice(&string_null_pad(&self.title, 51)?);
+        writer.write_all(&pad_terminated(&self.title, 51)?)?;
         // LGR name.
-        buffer.extend_from_slice(&string_null_pad(&self.lgr, 16)?);
+        writer.write_all(&pad_terminated(&self.lgr, 16)?)?;
         // Ground name.
-        buffer.extend_from_slice(&string_null_pad(&self.ground, 10)?);
+        writer.write_all(&pad_terminated(&self.ground, 10)?)?;
         // Sky name.
-        buffer.extend_from_slice(&string_null_pad(&self.sky, 10)?);
+        writer.write_all(&pad_terminated(&self.sky, 10)?)?;
 
         // Polygons.
-        buffer.extend_from_slice(&self.write_polygons()?);
+        self.write_polygons(&mut writer)?;
         // Objects.
-        buffer.extend_from_slice(&self.write_objects()?);
+        self.write_objects(&mut writer)?;
         // Pictures.
-        buffer.extend_from_slice(&self.write_pictures()?);
+        self.write_pictures(&mut writer)?;
 
         // EOD marker.
-        buffer.write_i32::<LE>(EOD)?;
+        writer.write_i32::<LE>(EOD)?;
 
         // Top10 lists.
         match top_10 {
@@ -567,19 +786,18 @@ impl Level {
                 best_times.multi.sort();
                 // Encrypt the data before writing.
                 let top10_bytes = write_top10(&best_times)?;
-                buffer.extend_from_slice(&crypt_top10(&top10_bytes));
+                writer.write_all(&crypt_top10(&top10_bytes))?;
             }
-            Top10Save::No => buffer.extend(crypt_top10(&[0; TOP10_SIZE])),
+            Top10Save::No => writer.write_all(&crypt_top10(&[0; TOP10_SIZE]))?,
         }
 
         // EOF marker.
-        buffer.write_i32::<LE>(EOF)?;
+        writer.write_i32::<LE>(EOF)?;
 
-        Ok(buffer)
+        Ok(())
     }
 
-    fn write_polygons(&self) -> Result<Vec<u8>, ElmaError> {
-        let mut buffer = vec![];
+    fn write_polygons<W: Write>(&self, buffer: &mut W) -> Result<(), ElmaError> {
         // Number of polygons.
         buffer.write_f64::<LE>(self.polygons.len() as f64 + 0.464_364_3_f64)?;
         for poly in &self.polygons {
@@ -593,11 +811,10 @@ impl Level {
                 buffer.write_f64::<LE>(-vertex.y)?;
             }
         }
-        Ok(buffer)
+        Ok(())
     }
 
-    fn write_objects(&self) -> Result<Vec<u8>, ElmaError> {
-        let mut buffer = vec![];
+    fn write_objects<W: Write>(&self, buffer: &mut W) -> Result<(), ElmaError> {
         // Number of objects.
         buffer.write_f64::<LE>(self.objects.len() as f64 + 0.464_364_3_f64)?;
         for obj in &self.objects {
@@ -637,20 +854,26 @@ impl Level {
                 _ => 0,
             })?;
         }
-        Ok(buffer)
+        Ok(())
     }
 
-    fn write_pictures(&self) -> Result<Vec<u8>, ElmaError> {
-        let mut buffer = vec![];
+    fn write_pictures<W: Write>(&self, buffer: &mut W) -> Result<(), ElmaError> {
         // Number of pictures.
         buffer.write_f64::<LE>(self.pictures.len() as f64 + 0.234_567_2_f64)?;
         for pic in &self.pictures {
+            let (name, texture, mask) = match pic.kind {
+                PictureKind::Normal { ref name } => (name.as_str(), "", ""),
+                PictureKind::Texture {
+                    ref texture,
+                    ref mask,
+                } => ("", texture.as_str(), mask.as_str()),
+            };
             // Picture name.
-            buffer.extend_from_slice(&string_null_pad(&pic.name, 10)?);
+            buffer.write_all(&pad_terminated(name, 10)?)?;
             // Texture name.
-            buffer.extend_from_slice(&string_null_pad(&pic.texture, 10)?);
+            buffer.write_all(&pad_terminated(texture, 10)?)?;
             // Mask name.
-            buffer.extend_from_slice(&string_null_pad(&pic.mask, 10)?);
+            buffer.write_all(&pad_terminated(mask, 10)?)?;
             // Position.
             buffer.write_f64::<LE>(pic.position.x)?;
             buffer.write_f64::<LE>(-pic.position.y)?;
@@ -663,7 +886,7 @@ impl Level {
                 Clip::Sky => 2,
             })?;
         }
-        Ok(buffer)
+        Ok(())
     }
 
     /// Width of level based on left- and right-most vertices.
@@ -694,6 +917,68 @@ impl Level {
         Ok(())
     }
 
+    /// Checks that every field can be written to a level file, without writing anything.
+    /// Returns all offending fields at once instead of stopping at the first one like
+    /// `to_bytes` does.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// let mut level = Level::new();
+    /// level.lgr = "extremely long lgr name".into();
+    /// let errors = level.validate().unwrap_err();
+    /// assert_eq!(errors[0].field, "lgr");
+    /// assert_eq!(errors[0].kind, FieldErrorKind::TooLong { length: 23, max: 15 });
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = vec![];
+        check_string(&mut errors, "title".into(), &self.title, 50);
+        check_string(&mut errors, "lgr".into(), &self.lgr, 15);
+        check_string(&mut errors, "ground".into(), &self.ground, 9);
+        check_string(&mut errors, "sky".into(), &self.sky, 9);
+
+        for (n, object) in self.objects.iter().enumerate() {
+            if let ObjectType::Apple { animation, .. } = object.object_type {
+                check_range(
+                    &mut errors,
+                    format!("objects[{}].animation", n),
+                    animation,
+                    1,
+                    9,
+                );
+            }
+        }
+
+        for (n, picture) in self.pictures.iter().enumerate() {
+            match picture.kind {
+                PictureKind::Normal { ref name } => {
+                    check_string(&mut errors, format!("pictures[{}].name", n), name, 9);
+                }
+                PictureKind::Texture {
+                    ref texture,
+                    ref mask,
+                } => {
+                    check_string(&mut errors, format!("pictures[{}].texture", n), texture, 9);
+                    check_string(&mut errors, format!("pictures[{}].mask", n), mask, 9);
+                }
+            }
+            check_range(
+                &mut errors,
+                format!("pictures[{}].distance", n),
+                picture.distance,
+                0,
+                i32::MAX,
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Returns a vector with the indexes of polygons containing too few vertices.
     fn check_vertex_count(&self) -> Result<(), TopologyError> {
         let mut error_polygons = vec![];
@@ -711,16 +996,20 @@ impl Level {
     }
 
     fn check_objects(&self) -> Result<(), TopologyError> {
-        if self.polygons.len() > 1000 {
-            return Err(TopologyError::MaxPolygons(&self.polygons.len() - 1000));
+        if self.polygons.len() > MAX_POLYGONS {
+            return Err(TopologyError::MaxPolygons(
+                self.polygons.len() - MAX_POLYGONS,
+            ));
         }
 
-        if self.objects.len() > 252 {
-            return Err(TopologyError::MaxObjects(&self.objects.len() - 252));
+        if self.objects.len() > MAX_OBJECTS {
+            return Err(TopologyError::MaxObjects(self.objects.len() - MAX_OBJECTS));
         }
 
-        if self.pictures.len() > 5000 {
-            return Err(TopologyError::MaxPictures(&self.pictures.len() - 5000));
+        if self.pictures.len() > MAX_PICTURES {
+            return Err(TopologyError::MaxPictures(
+                self.pictures.len() - MAX_PICTURES,
+            ));
         }
 
         let player_count = self.objects.iter().fold(0, |total, object| {
@@ -825,6 +1114,41 @@ impl Level {
     }
 }
 
+// Pads a string to its field size, always leaving room for the null terminator Elma reads it up
+// to.
+fn pad_terminated(name: &str, size: usize) -> Result<Vec<u8>, ElmaError> {
+    let mut bytes = string_null_pad(name, size - 1)?;
+    bytes.push(0);
+    Ok(bytes)
+}
+
+fn check_string(errors: &mut Vec<FieldError>, field: String, value: &str, max: usize) {
+    if !value.is_ascii() {
+        errors.push(FieldError {
+            field: field.clone(),
+            kind: FieldErrorKind::NonASCII,
+        });
+    }
+    if value.len() > max {
+        errors.push(FieldError {
+            field,
+            kind: FieldErrorKind::TooLong {
+                length: value.len(),
+                max,
+            },
+        });
+    }
+}
+
+fn check_range(errors: &mut Vec<FieldError>, field: String, value: i32, min: i32, max: i32) {
+    if !(min..=max).contains(&value) {
+        errors.push(FieldError {
+            field,
+            kind: FieldErrorKind::OutOfRange { value, min, max },
+        });
+    }
+}
+
 /// Decrypt and encrypt top10 list data. Same algorithm for both.
 pub fn crypt_top10(top10_data: &[u8]) -> Vec<u8> {
     let mut top10: Vec<u8> = Vec::with_capacity(TOP10_SIZE);
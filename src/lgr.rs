@@ -1,9 +1,11 @@
 use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use std::fs;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 
 use super::{
-    utils::{string_null_pad, trim_string}, Clip, ElmaError,
+    utils::{string_null_pad, trim_string, ByteReader},
+    Clip, ElmaError,
 };
 
 // Magic arbitrary number to signify start of LGR file.
@@ -136,10 +138,26 @@ impl LGR {
         Self::parse_lgr(buffer.as_ref())
     }
 
+    /// Reads a LGR from a reader.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lgr::*;
+    /// let file = std::fs::File::open("tests/assets/lgr/Default.lgr").unwrap();
+    /// let lgr = LGR::read_from(file).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ElmaError> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        Self::parse_lgr(&buffer)
+    }
+
     fn parse_lgr(buffer: &[u8]) -> Result<Self, ElmaError> {
         let mut lgr = Self::new();
+        let mut reader = ByteReader::new(buffer);
 
-        let (version, mut buffer) = buffer.split_at(5);
+        let version = reader.take(5, "header")?;
         // there are no other LGR versions possible, so no need to store it (?)
         if version != b"LGR12" {
             return Err(ElmaError::InvalidLGRFile(LGRError::InvalidVersion(
@@ -147,8 +165,8 @@ impl LGR {
             )));
         }
 
-        let picture_len = buffer.read_u32::<LE>()? as usize;
-        let expected_header = buffer.read_i32::<LE>()?;
+        let picture_len = reader.read_u32("header")? as usize;
+        let expected_header = reader.read_i32("header")?;
         if expected_header != LGR {
             return Err(ElmaError::InvalidLGRFile(LGRError::InvalidHeader(
                 expected_header,
@@ -156,16 +174,14 @@ impl LGR {
         }
 
         // picture.lst section
-        let list_len = buffer.read_u32::<LE>()? as usize;
-        lgr.parse_list_data(&buffer, list_len)?;
-        let (_, buffer) = buffer.split_at(26 * list_len);
+        let list_len = reader.read_u32("picture list")? as usize;
+        let list_data = reader.take_records(list_len, 26, "picture list")?;
+        lgr.parse_list_data(list_data, list_len)?;
 
         // pcx data
-        let bytes_read = lgr.parse_picture_data(&buffer, picture_len)?;
+        lgr.parse_picture_data(&mut reader, picture_len)?;
 
-        let (_, mut expected_eof) = buffer.split_at(bytes_read);
-
-        let expected_eof = expected_eof.read_i32::<LE>()?;
+        let expected_eof = reader.read_i32("end of file")?;
         if expected_eof != LGR_EOF {
             return Err(ElmaError::EOFMismatch);
         }
@@ -216,22 +232,17 @@ impl LGR {
         Ok(())
     }
 
-    fn parse_picture_data(&mut self, mut buffer: &[u8], len: usize) -> Result<usize, ElmaError> {
-        let mut bytes_read = 0;
+    fn parse_picture_data(&mut self, reader: &mut ByteReader, len: usize) -> Result<(), ElmaError> {
         // pcx data
         for _ in 0..len {
-            let (name, remaining) = buffer.split_at(12);
-            let name = trim_string(&name)?;
-            let (_, remaining) = remaining.split_at(8);
-            let (mut bytes_len, remaining) = remaining.split_at(4);
-            let bytes_len = bytes_len.read_i32::<LE>()? as usize;
-            let data = remaining[..bytes_len].to_vec();
+            let name = reader.read_string(12, "picture data")?;
+            reader.take(8, "picture data")?;
+            let bytes_len = reader.read_i32("picture data")? as usize;
+            let data = reader.take(bytes_len, "picture data")?.to_vec();
 
             self.picture_data.push(PictureData { name, data });
-            buffer = &buffer[24 + bytes_len..];
-            bytes_read += 24 + bytes_len;
         }
-        Ok(bytes_read)
+        Ok(())
     }
 
     /// Returns a Vec with bytes representing the LGR as a buffer.
@@ -245,52 +256,66 @@ impl LGR {
     /// ```
     pub fn to_bytes(&self) -> Result<Vec<u8>, ElmaError> {
         let mut bytes = vec![];
-        bytes.extend_from_slice(b"LGR12");
-        bytes.write_u32::<LE>(self.picture_data.len() as u32)?;
-        bytes.write_i32::<LE>(LGR)?;
-        bytes.extend_from_slice(&self.write_picture_list()?);
-        bytes.extend_from_slice(&self.write_picture_data()?);
-        bytes.write_i32::<LE>(LGR_EOF)?;
-
+        self.write_to(&mut bytes)?;
         Ok(bytes)
     }
 
-    fn write_picture_list(&self) -> Result<Vec<u8>, ElmaError> {
-        let mut bytes = vec![];
-        bytes.write_u32::<LE>(self.picture_list.len() as u32)?;
-        let mut names = vec![];
-        let mut picture_types = vec![];
-        let mut distances = vec![];
-        let mut clippings = vec![];
-        let mut transparencies = vec![];
+    /// Writes the LGR in its binary form to a writer.
+    ///
+    /// The LGR is written piece by piece as it is encoded, so wrap unbuffered writers such as
+    /// files in a `BufWriter`. A picture name that cannot be encoded fails only once the data
+    /// before it has been written; use `to_bytes` to encode all of it before writing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lgr::*;
+    /// let lgr = LGR::new();
+    /// let mut buffer = vec![];
+    /// lgr.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, lgr.to_bytes().unwrap());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), ElmaError> {
+        writer.write_all(b"LGR12")?;
+        writer.write_u32::<LE>(self.picture_data.len() as u32)?;
+        writer.write_i32::<LE>(LGR)?;
+        self.write_picture_list(&mut writer)?;
+        self.write_picture_data(&mut writer)?;
+        writer.write_i32::<LE>(LGR_EOF)?;
 
+        Ok(())
+    }
+
+    fn write_picture_list<W: Write>(&self, writer: &mut W) -> Result<(), ElmaError> {
+        writer.write_u32::<LE>(self.picture_list.len() as u32)?;
+        // Fields are stored column by column.
         for picture in &self.picture_list {
-            names.extend_from_slice(&string_null_pad(&picture.name, 10)?);
-            picture_types.write_u32::<LE>(picture.picture_type as u32)?;
-            distances.write_u32::<LE>(u32::from(picture.distance))?;
-            clippings.write_u32::<LE>(picture.clipping as u32)?;
-            transparencies.write_u32::<LE>(picture.transparency as u32)?;
+            writer.write_all(&string_null_pad(&picture.name, 10)?)?;
+        }
+        for picture in &self.picture_list {
+            writer.write_u32::<LE>(picture.picture_type as u32)?;
+        }
+        for picture in &self.picture_list {
+            writer.write_u32::<LE>(u32::from(picture.distance))?;
+        }
+        for picture in &self.picture_list {
+            writer.write_u32::<LE>(picture.clipping as u32)?;
+        }
+        for picture in &self.picture_list {
+            writer.write_u32::<LE>(picture.transparency as u32)?;
         }
 
-        bytes.extend_from_slice(&names);
-        bytes.extend_from_slice(&picture_types);
-        bytes.extend_from_slice(&distances);
-        bytes.extend_from_slice(&clippings);
-        bytes.extend_from_slice(&transparencies);
-
-        Ok(bytes)
+        Ok(())
     }
 
-    fn write_picture_data(&self) -> Result<Vec<u8>, ElmaError> {
-        let mut bytes = vec![];
-
+    fn write_picture_data<W: Write>(&self, writer: &mut W) -> Result<(), ElmaError> {
         for picture in &self.picture_data {
-            bytes.extend_from_slice(&string_null_pad(&picture.name, 20)?);
-            bytes.write_u32::<LE>(picture.data.len() as u32)?;
-            bytes.extend_from_slice(&picture.data);
+            writer.write_all(&string_null_pad(&picture.name, 20)?)?;
+            writer.write_u32::<LE>(picture.data.len() as u32)?;
+            writer.write_all(&picture.data)?;
         }
 
-        Ok(bytes)
+        Ok(())
     }
 
     /// Save the LGR to a file.
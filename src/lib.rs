@@ -11,22 +11,50 @@ extern crate itertools;
 
 use std::{io, string};
 
+/// Build levels from ASCII-art grids.
+pub mod ascii;
+/// Create levels from black-and-white bitmaps.
+pub mod bitmap;
 /// Various constant values used throughout the game and library.
 pub mod constants;
+/// Import and export levels as ASCII DXF drawings.
+pub mod dxf;
+/// Reversible level edits with undo and redo history.
+pub mod edit;
+/// Polygon geometry operations for level construction.
+pub mod geometry;
+/// Automatic grass generation along ground edges.
+pub mod grass;
 /// Read and write Elasto Mania level files.
 pub mod lev;
 /// Read and write Elasto Mania LGR files.
 pub mod lgr;
+/// Triangulate level polygons into meshes for rendering.
+pub mod mesh;
 /// Read and write Elasto Mania replay files.
 pub mod rec;
+/// Clean up degenerate level polygons.
+pub mod repair;
+/// Select, copy and paste parts of levels.
+pub mod select;
+/// Spatial index over level edges for nearest edge and raycast queries.
+pub mod spatial;
 /// Read and write Elasto Mania state.dat files.
 pub mod state;
+/// Import level polygons and objects from SVG drawings.
+pub mod svg;
+/// Write text in levels with a built-in stroke font.
+pub mod text;
 /// Various utility functions.
 pub mod utils;
 
 mod shared;
+use ascii::AsciiError;
+use bitmap::BitmapError;
+use dxf::DxfError;
 use lgr::LGRError;
 pub use shared::{BestTimes, Clip, Position, Time, TimeEntry, Version};
+use svg::SvgError;
 
 /// General errors.
 #[derive(Debug, PartialEq)]
@@ -43,6 +71,14 @@ pub enum ElmaError {
     InvalidStateFile,
     /// Invalid LGR file.
     InvalidLGRFile(LGRError),
+    /// Invalid SVG drawing.
+    InvalidSvg(SvgError),
+    /// Invalid bitmap image.
+    InvalidBitmap(BitmapError),
+    /// Invalid DXF drawing.
+    InvalidDxf(DxfError),
+    /// Invalid ASCII level grid.
+    InvalidAscii(AsciiError),
     /// Invalid gravity value.
     InvalidGravity(i32),
     /// Invalid object value.
@@ -67,6 +103,16 @@ pub enum ElmaError {
     Io(std::io::ErrorKind),
     /// String errors from std::String.
     StringFromUtf8(usize),
+    /// Data ended in the middle of a section. `offset` is the byte offset of the read that
+    /// could not be completed.
+    UnexpectedEof {
+        /// Byte offset of the incomplete read.
+        offset: usize,
+        /// Name of the file section being read.
+        section: &'static str,
+    },
+    /// Top10 list claims more than 10 or a negative number of entries.
+    InvalidTop10Count(i32),
 }
 
 impl From<io::Error> for ElmaError {
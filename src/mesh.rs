@@ -0,0 +1,351 @@
+use super::{
+    geometry::{area, cross, difference, union},
+    lev::{Level, Polygon},
+    Position,
+};
+
+/// Triangle mesh.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mesh {
+    /// Vertex positions.
+    pub vertices: Vec<Position<f64>>,
+    /// Vertex indices, three per triangle. Triangles are counter-clockwise.
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    /// Creates an empty mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Total area of the triangles.
+    pub fn area(&self) -> f64 {
+        self.indices
+            .chunks(3)
+            .map(|t| {
+                area(&[
+                    self.vertices[t[0] as usize],
+                    self.vertices[t[1] as usize],
+                    self.vertices[t[2] as usize],
+                ])
+            })
+            .sum()
+    }
+
+    // Adds a ring of vertices and returns their indices.
+    fn add_ring(&mut self, ring: &[Position<f64>]) -> Vec<usize> {
+        let start = self.vertices.len();
+        self.vertices.extend_from_slice(ring);
+        (start..self.vertices.len()).collect()
+    }
+}
+
+/// Air, ground and grass meshes of a level.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LevelMeshes {
+    /// The inside of the level, where the bike rides: the region inside an odd number of
+    /// polygons, as decided by `geometry::contains`.
+    pub air: Mesh,
+    /// Everything else within a rectangle around the polygons.
+    pub ground: Mesh,
+    /// The inside of every grass polygon.
+    pub grass: Mesh,
+}
+
+/// Triangulates the air, ground and grass of a level.
+///
+/// Polygons other than grass are combined with the even-odd rule, so nested polygons become
+/// holes and overlapping or self-intersecting polygons are resolved first. Ground goes on
+/// forever in Elma, so the ground mesh fills a rectangle reaching `margin` units past the
+/// polygons on every side, with the air cut out of it. Each grass polygon is triangulated on
+/// its own.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::lev::*;
+/// # use elma::mesh::*;
+/// let meshes = triangulate_level(&Level::new(), 1.);
+/// assert_eq!(meshes.air.triangle_count(), 2);
+/// assert_eq!(meshes.air.area(), 70.);
+/// assert_eq!(meshes.ground.area(), 12. * 9. - 70.);
+/// assert_eq!(meshes.grass.triangle_count(), 0);
+/// ```
+pub fn triangulate_level(level: &Level, margin: f64) -> LevelMeshes {
+    let (grass, air): (Vec<_>, Vec<_>) = level.polygons.iter().partition(|p| p.grass);
+    let air: Vec<Polygon> = air.into_iter().cloned().collect();
+
+    let mut grass_mesh = Mesh::new();
+    for polygon in grass.into_iter().filter(|p| p.vertices.len() > 2) {
+        let mut ring = polygon.vertices.clone();
+        if area(&ring) < 0. {
+            ring.reverse();
+        }
+        let indices = grass_mesh.add_ring(&ring);
+        ear_clip(&mut grass_mesh, indices);
+    }
+
+    let vertices = || air.iter().flat_map(|p| &p.vertices);
+    let ground = if vertices().next().is_some() {
+        let min_x = vertices().map(|v| v.x).fold(f64::INFINITY, f64::min) - margin;
+        let max_x = vertices().map(|v| v.x).fold(f64::NEG_INFINITY, f64::max) + margin;
+        let min_y = vertices().map(|v| v.y).fold(f64::INFINITY, f64::min) - margin;
+        let max_y = vertices().map(|v| v.y).fold(f64::NEG_INFINITY, f64::max) + margin;
+        let frame = Polygon {
+            grass: false,
+            vertices: vec![
+                Position::new(min_x, min_y),
+                Position::new(max_x, min_y),
+                Position::new(max_x, max_y),
+                Position::new(min_x, max_y),
+            ],
+        };
+        triangulate(&difference(&[frame], &air))
+    } else {
+        Mesh::new()
+    };
+
+    LevelMeshes {
+        air: triangulate(&air),
+        ground,
+        grass: grass_mesh,
+    }
+}
+
+/// Triangulates the region inside an odd number of the polygons, which is air in a level.
+/// Grass polygons are ignored.
+pub fn triangulate(polygons: &[Polygon]) -> Mesh {
+    // Simple rings with the region on their left: outer boundaries go counter-clockwise and
+    // holes clockwise.
+    let rings: Vec<Vec<Position<f64>>> = union(polygons, &[])
+        .into_iter()
+        .map(|p| p.vertices)
+        .collect();
+    let (outers, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|r| area(r) > 0.);
+
+    // Each hole belongs to the smallest outer ring around it.
+    let mut outer_holes: Vec<Vec<Vec<Position<f64>>>> = vec![vec![]; outers.len()];
+    for hole in holes {
+        let parent = (0..outers.len())
+            .filter(|&n| hole.iter().any(|v| strictly_inside(&outers[n], v)))
+            .min_by(|&a, &b| area(&outers[a]).total_cmp(&area(&outers[b])));
+        if let Some(parent) = parent {
+            outer_holes[parent].push(hole);
+        }
+    }
+
+    let mut mesh = Mesh::new();
+    for (outer, mut holes) in outers.into_iter().zip(outer_holes) {
+        let mut polygon = mesh.add_ring(&outer);
+        // Bridge holes from right to left, so each bridge only sees already merged holes.
+        let rightmost =
+            |ring: &Vec<Position<f64>>| ring.iter().map(|v| v.x).fold(f64::NEG_INFINITY, f64::max);
+        holes.sort_by(|a, b| rightmost(b).total_cmp(&rightmost(a)));
+        for hole in holes {
+            let hole = mesh.add_ring(&hole);
+            polygon = bridge(&mesh.vertices, polygon, hole);
+        }
+        ear_clip(&mut mesh, polygon);
+    }
+    mesh
+}
+
+// Whether the point is inside the ring and not on its boundary.
+fn strictly_inside(ring: &[Position<f64>], point: &Position<f64>) -> bool {
+    let n = ring.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (&ring[i], &ring[(i + 1) % n]);
+        if cross(b.x - a.x, b.y - a.y, point.x - a.x, point.y - a.y) == 0.
+            && point.x >= a.x.min(b.x)
+            && point.x <= a.x.max(b.x)
+            && point.y >= a.y.min(b.y)
+            && point.y <= a.y.max(b.y)
+        {
+            return false;
+        }
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) * (b.x - a.x) / (b.y - a.y)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+// Cuts from the rightmost vertex of the hole to a visible vertex of the polygon, joining both
+// into one ring that goes around the hole and back along the cut.
+fn bridge(vertices: &[Position<f64>], polygon: Vec<usize>, hole: Vec<usize>) -> Vec<usize> {
+    let (start, &m) = hole
+        .iter()
+        .enumerate()
+        .max_by(|a, b| {
+            let (pa, pb) = (vertices[*a.1], vertices[*b.1]);
+            pa.x.total_cmp(&pb.x).then(pb.y.total_cmp(&pa.y))
+        })
+        .unwrap();
+    let mp = vertices[m];
+    let n = polygon.len();
+
+    // Closest edge crossed by a ray going right from the hole vertex.
+    let mut closest: Option<(f64, usize)> = None;
+    for i in 0..n {
+        let (a, b) = (vertices[polygon[i]], vertices[polygon[(i + 1) % n]]);
+        // Going right from the inside, the ray leaves through an edge going up, which has the
+        // inside on its left.
+        if a.y > mp.y || b.y < mp.y || a.y == b.y {
+            continue;
+        }
+        let x = a.x + (mp.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        if x >= mp.x && closest.is_none_or(|(cx, _)| x < cx) {
+            closest = Some((x, i));
+        }
+    }
+    let (x, edge) = match closest {
+        Some(found) => found,
+        // Not actually inside; leave the hole out.
+        None => return polygon,
+    };
+    let (a, b) = (edge, (edge + 1) % n);
+    let hit = Position::new(x, mp.y);
+    let mut visible = if vertices[polygon[a]].x > vertices[polygon[b]].x {
+        a
+    } else {
+        b
+    };
+    if vertices[polygon[a]] == hit {
+        visible = a;
+    } else if vertices[polygon[b]] == hit {
+        visible = b;
+    } else {
+        // Reflex vertices inside the triangle between the hole vertex, the hit and the chosen
+        // vertex would block the view; take the one closest in angle to the ray instead.
+        let p = vertices[polygon[visible]];
+        let mut best = (f64::INFINITY, f64::INFINITY);
+        for i in 0..n {
+            let v = vertices[polygon[i]];
+            if i == visible || v == p || !is_reflex(vertices, &polygon, i) {
+                continue;
+            }
+            if in_triangle(&mp, &hit, &p, &v) || in_triangle(&mp, &p, &hit, &v) {
+                let angle = (v.y - mp.y).abs().atan2(v.x - mp.x);
+                let distance = (v.x - mp.x).hypot(v.y - mp.y);
+                if (angle, distance) < best {
+                    best = (angle, distance);
+                    visible = i;
+                }
+            }
+        }
+    }
+
+    // Earlier bridges duplicate vertices; use the copy whose corner faces the hole.
+    let target = vertices[polygon[visible]];
+    if let Some(i) = (0..n).find(|&i| {
+        vertices[polygon[i]] == target
+            && in_cone(
+                &vertices[polygon[(i + n - 1) % n]],
+                &target,
+                &vertices[polygon[(i + 1) % n]],
+                &mp,
+            )
+    }) {
+        visible = i;
+    }
+
+    let mut merged = Vec::with_capacity(n + hole.len() + 2);
+    merged.extend_from_slice(&polygon[..=visible]);
+    merged.extend(hole[start..].iter().chain(&hole[..=start]));
+    merged.extend_from_slice(&polygon[visible..]);
+    merged
+}
+
+fn is_reflex(vertices: &[Position<f64>], polygon: &[usize], i: usize) -> bool {
+    let n = polygon.len();
+    let (a, b, c) = (
+        vertices[polygon[(i + n - 1) % n]],
+        vertices[polygon[i]],
+        vertices[polygon[(i + 1) % n]],
+    );
+    cross(b.x - a.x, b.y - a.y, c.x - b.x, c.y - b.y) < 0.
+}
+
+// Whether `m` is within the inside corner of a counter-clockwise polygon at `p`.
+fn in_cone(
+    prev: &Position<f64>,
+    p: &Position<f64>,
+    next: &Position<f64>,
+    m: &Position<f64>,
+) -> bool {
+    let left_of = |a: &Position<f64>, b: &Position<f64>| {
+        cross(b.x - a.x, b.y - a.y, m.x - a.x, m.y - a.y) > 0.
+    };
+    if cross(p.x - prev.x, p.y - prev.y, next.x - p.x, next.y - p.y) >= 0. {
+        left_of(prev, p) && left_of(p, next)
+    } else {
+        left_of(prev, p) || left_of(p, next)
+    }
+}
+
+// Whether `p` is inside or on the counter-clockwise triangle a-b-c.
+fn in_triangle(a: &Position<f64>, b: &Position<f64>, c: &Position<f64>, p: &Position<f64>) -> bool {
+    cross(b.x - a.x, b.y - a.y, p.x - a.x, p.y - a.y) >= 0.
+        && cross(c.x - b.x, c.y - b.y, p.x - b.x, p.y - b.y) >= 0.
+        && cross(a.x - c.x, a.y - c.y, p.x - c.x, p.y - c.y) >= 0.
+}
+
+// Ear clipping of a counter-clockwise ring, which may touch itself along hole bridges.
+fn ear_clip(mesh: &mut Mesh, mut polygon: Vec<usize>) {
+    let mut i = 0;
+    let mut misses = 0;
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let (ia, ib, ic) = (
+            polygon[(i + n - 1) % n],
+            polygon[i % n],
+            polygon[(i + 1) % n],
+        );
+        let (a, b, c) = (mesh.vertices[ia], mesh.vertices[ib], mesh.vertices[ic]);
+        let turn = cross(b.x - a.x, b.y - a.y, c.x - b.x, c.y - b.y);
+
+        // After a full round without ears, only degenerate corners are left; clip anyway.
+        let ear = if misses >= n {
+            true
+        } else {
+            turn > 0.
+                && !polygon.iter().any(|&v| {
+                    let p = mesh.vertices[v];
+                    p != a && p != b && p != c && in_triangle(&a, &b, &c, &p)
+                })
+        };
+
+        if ear {
+            if turn > 0. {
+                mesh.indices.extend(&[ia as u32, ib as u32, ic as u32]);
+            }
+            polygon.remove(i % n);
+            misses = 0;
+            // Step back so the previous corner, now changed, is tested again.
+            i = (i % n + n - 2) % (n - 1);
+        } else {
+            i = (i + 1) % n;
+            misses += 1;
+        }
+    }
+    if polygon.len() == 3 {
+        let (a, b, c) = (
+            mesh.vertices[polygon[0]],
+            mesh.vertices[polygon[1]],
+            mesh.vertices[polygon[2]],
+        );
+        if cross(b.x - a.x, b.y - a.y, c.x - b.x, c.y - b.y) > 0. {
+            mesh.indices
+                .extend(&[polygon[0] as u32, polygon[1] as u32, polygon[2] as u32]);
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use super::{utils::string_null_pad, ElmaError, Position};
+use super::{
+    lev::{Level, Object, ObjectType},
+    utils::string_null_pad,
+    ElmaError, Position, Time,
+};
 use byteorder::{WriteBytesExt, LE};
 use nom::le_f32;
 use nom::le_f64;
@@ -9,7 +13,9 @@ use nom::le_u8;
 use nom::verbose_errors::Context::List;
 use nom::Err::Failure;
 use nom::ErrorKind::Custom;
+use std::f64;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use utils::boolean;
 use utils::null_padded_string;
@@ -20,9 +26,19 @@ const END_OF_PLAYER: i32 = 0x00_49_2F_75;
 const EVENT_ERROR: u32 = 1;
 // Replay version number that all valid replays need to have.
 const REPLAY_VERSION: u32 = 0x83;
+// Milliseconds per unit of event time.
+const EVENT_TIME_SCALE: f64 = 2_289.377_289_38;
+// Milliseconds between frames, which are recorded at 30 frames per second.
+const FRAME_TIME: f64 = 33.333;
+// Wheel and head offsets are stored in thousandths of a unit.
+const OFFSET_SCALE: f64 = 1000.;
+// Bike rotation value for a full turn.
+const FULL_ROTATION: f64 = 10000.;
+// Wheel rotation value for a full turn.
+const FULL_WHEEL_ROTATION: f64 = 250.;
 
 /// Bike direction.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Direction {
     /// Right.
     Right,
@@ -39,25 +55,30 @@ impl Default for Direction {
 /// One frame of replay.
 #[derive(Debug, Default, PartialEq)]
 pub struct Frame {
-    /// Bike position.
+    /// Bike position in world coordinates.
     pub bike: Position<f32>,
-    /// Left wheel position.
+    /// Left wheel position relative to the bike, in thousandths of a unit. See
+    /// `left_wheel_position`.
     pub left_wheel: Position<i16>,
-    /// Right wheel position.
+    /// Right wheel position relative to the bike, in thousandths of a unit. See
+    /// `right_wheel_position`.
     pub right_wheel: Position<i16>,
-    /// Head position.
+    /// Head position relative to the bike, in thousandths of a unit. See `head_position`.
     pub head: Position<i16>,
-    /// Bike rotation. Range 0..10000.
+    /// Bike rotation in ten-thousandths of a full turn. Range 0..=10000. See `rotation_angle`.
     pub rotation: i16,
-    /// Left wheel rotation. Range 0..255.
+    /// Left wheel rotation in 250ths of a full turn. Range 0..=250. See `left_wheel_angle`.
     pub left_wheel_rotation: u8,
-    /// Right wheel rotation. Range 0..255.
+    /// Right wheel rotation in 250ths of a full turn. Range 0..=250. See `right_wheel_angle`.
     pub right_wheel_rotation: u8,
-    /// State of throttle and direction.
+    /// State of throttle and direction. Bit 0 is set while throttling and bit 1 while facing
+    /// right; see `throttle` and `direction`. The other bits have no known meaning and are kept
+    /// as read, so replays are written back unchanged.
     pub throttle_and_dir: u8,
-    /// Rotation speed of back wheel.
+    /// Rotation speed of back wheel, which sets the pitch of the engine sound. See
+    /// `back_wheel_speed`.
     pub back_wheel_rot_speed: u8,
-    /// Collision strength.
+    /// Collision strength, which sets the volume of the bump sound. See `collision_volume`.
     pub collision_strength: u8,
 }
 
@@ -87,6 +108,146 @@ impl Frame {
             Direction::Left
         }
     }
+
+    /// Bike position in world coordinates.
+    pub fn bike_position(&self) -> Position<f64> {
+        Position::new(f64::from(self.bike.x), f64::from(self.bike.y))
+    }
+
+    /// Left wheel position in world coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// # use elma::Position;
+    /// let frame = Frame {
+    ///     bike: Position::new(10., 5.),
+    ///     left_wheel: Position::new(-850, -600),
+    ///     ..Frame::new()
+    /// };
+    /// assert_eq!(frame.left_wheel_position(), Position::new(9.15, 4.4));
+    /// ```
+    pub fn left_wheel_position(&self) -> Position<f64> {
+        self.offset_position(&self.left_wheel)
+    }
+
+    /// Right wheel position in world coordinates.
+    pub fn right_wheel_position(&self) -> Position<f64> {
+        self.offset_position(&self.right_wheel)
+    }
+
+    /// Head position in world coordinates.
+    pub fn head_position(&self) -> Position<f64> {
+        self.offset_position(&self.head)
+    }
+
+    /// Bike rotation in radians, counter-clockwise. Range 0..2π.
+    pub fn rotation_angle(&self) -> f64 {
+        turn_angle(f64::from(self.rotation) / FULL_ROTATION)
+    }
+
+    /// Left wheel rotation in radians. Range 0..2π.
+    pub fn left_wheel_angle(&self) -> f64 {
+        turn_angle(f64::from(self.left_wheel_rotation) / FULL_WHEEL_ROTATION)
+    }
+
+    /// Right wheel rotation in radians. Range 0..2π.
+    pub fn right_wheel_angle(&self) -> f64 {
+        turn_angle(f64::from(self.right_wheel_rotation) / FULL_WHEEL_ROTATION)
+    }
+
+    /// Back wheel rotation speed as a fraction of the largest stored value. Range 0..=1.
+    pub fn back_wheel_speed(&self) -> f64 {
+        f64::from(self.back_wheel_rot_speed) / f64::from(u8::MAX)
+    }
+
+    /// Collision strength as a fraction of the largest stored value, where 0 is no collision.
+    /// Range 0..=1.
+    pub fn collision_volume(&self) -> f64 {
+        f64::from(self.collision_strength) / f64::from(u8::MAX)
+    }
+
+    fn offset_position(&self, offset: &Position<i16>) -> Position<f64> {
+        let bike = self.bike_position();
+        Position::new(
+            bike.x + f64::from(offset.x) / OFFSET_SCALE,
+            bike.y + f64::from(offset.y) / OFFSET_SCALE,
+        )
+    }
+}
+
+// Smallest turn from one angle to another, in radians. Range -π..π.
+fn angle_difference(from: f64, to: f64) -> f64 {
+    (to - from + f64::consts::PI).rem_euclid(2. * f64::consts::PI) - f64::consts::PI
+}
+
+// Angle in radians for a fraction of a full turn. A full turn is sometimes stored as is; it is
+// the same as no turn.
+fn turn_angle(turns: f64) -> f64 {
+    turns.rem_euclid(1.) * 2. * f64::consts::PI
+}
+
+/// Bike state decoded from a frame, or interpolated between frames by `Ride::state_at`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BikeState {
+    /// Bike position.
+    pub bike: Position<f64>,
+    /// Left wheel position.
+    pub left_wheel: Position<f64>,
+    /// Right wheel position.
+    pub right_wheel: Position<f64>,
+    /// Head position.
+    pub head: Position<f64>,
+    /// Bike rotation in radians, counter-clockwise. Range 0..2π.
+    pub rotation: f64,
+    /// Left wheel rotation in radians. Range 0..2π.
+    pub left_wheel_rotation: f64,
+    /// Right wheel rotation in radians. Range 0..2π.
+    pub right_wheel_rotation: f64,
+    /// Whether throttle is on.
+    pub throttle: bool,
+    /// Direction the bike is facing.
+    pub direction: Direction,
+}
+
+impl<'a> From<&'a Frame> for BikeState {
+    fn from(frame: &'a Frame) -> Self {
+        BikeState {
+            bike: frame.bike_position(),
+            left_wheel: frame.left_wheel_position(),
+            right_wheel: frame.right_wheel_position(),
+            head: frame.head_position(),
+            rotation: frame.rotation_angle(),
+            left_wheel_rotation: frame.left_wheel_angle(),
+            right_wheel_rotation: frame.right_wheel_angle(),
+            throttle: frame.throttle(),
+            direction: frame.direction(),
+        }
+    }
+}
+
+impl BikeState {
+    // State a fraction `t` of the way from this state to the next one. Angles take the shorter
+    // way around, and the throttle and direction are this state's.
+    fn interpolate(&self, next: &BikeState, t: f64) -> BikeState {
+        let lerp = |a: &Position<f64>, b: &Position<f64>| {
+            Position::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+        };
+        let angle =
+            |a: f64, b: f64| (a + angle_difference(a, b) * t).rem_euclid(2. * f64::consts::PI);
+        BikeState {
+            bike: lerp(&self.bike, &next.bike),
+            left_wheel: lerp(&self.left_wheel, &next.left_wheel),
+            right_wheel: lerp(&self.right_wheel, &next.right_wheel),
+            head: lerp(&self.head, &next.head),
+            rotation: angle(self.rotation, next.rotation),
+            left_wheel_rotation: angle(self.left_wheel_rotation, next.left_wheel_rotation),
+            right_wheel_rotation: angle(self.right_wheel_rotation, next.right_wheel_rotation),
+            throttle: self.throttle,
+            direction: self.direction,
+        }
+    }
 }
 
 #[derive(Debug, Default, PartialEq)]
@@ -156,6 +317,43 @@ pub(crate) struct ReplayHeader {
     pub level: String,
 }
 
+/// Bike motion at one frame of a ride, from `Ride::motion`. Lengths are in level units and
+/// rates are per second, while `time` is in milliseconds like the rest of `Ride`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Motion {
+    /// Time of the frame in milliseconds.
+    pub time: f64,
+    /// Bike velocity, in level units per second.
+    pub velocity: Position<f64>,
+    /// Bike speed, the length of the velocity, in level units per second.
+    pub speed: f64,
+    /// Rate of change of the speed in level units per second squared; negative when slowing
+    /// down.
+    pub acceleration: f64,
+    /// Rotation speed of the bike in radians per second, counter-clockwise.
+    pub angular_velocity: f64,
+}
+
+/// Apple taken during a ride, from `Ride::splits`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Split {
+    /// Time the apple was taken, in milliseconds.
+    pub time: f64,
+    /// Object index of the object touch event before the apple event, which is the apple in the
+    /// level's objects sorted as killers, apples, flowers and start.
+    pub object: Option<i16>,
+}
+
+/// Split by split comparison of two rides, from `Ride::compare_splits`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SplitComparison {
+    /// Time differences of the splits both rides have, in milliseconds. Negative where the
+    /// first ride took its apple earlier.
+    pub deltas: Vec<f64>,
+    /// Splits where the rides took a different apple.
+    pub reordered: Vec<usize>,
+}
+
 /// Player ride information (frames and events).
 #[derive(Debug, PartialEq, Default)]
 pub struct Ride {
@@ -176,7 +374,196 @@ impl Ride {
 
     /// Gets the time based on frame count.
     pub fn get_frame_time(&self) -> f64 {
-        self.frames.len() as f64 * 33.333
+        self.frames.len() as f64 * FRAME_TIME
+    }
+
+    /// Bike state at a time in milliseconds, interpolated between the frames around it. Frame
+    /// `n` is at `n * 33.333` milliseconds; times outside the ride give the first or last frame.
+    /// Returns `None` if the ride has no frames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let ride = &replay.rides[0];
+    /// let state = ride.state_at(50.).unwrap();
+    /// let (first, second) = (BikeState::from(&ride.frames[1]), BikeState::from(&ride.frames[2]));
+    /// assert!((state.bike.x - (first.bike.x + second.bike.x) / 2.).abs() < 1e-4);
+    /// ```
+    pub fn state_at(&self, time: f64) -> Option<BikeState> {
+        let last = self.frames.len().checked_sub(1)?;
+        let position = (time / FRAME_TIME).max(0.);
+        let index = position.floor() as usize;
+        if index >= last {
+            return Some(BikeState::from(&self.frames[last]));
+        }
+        let state = BikeState::from(&self.frames[index]);
+        Some(state.interpolate(
+            &BikeState::from(&self.frames[index + 1]),
+            position - index as f64,
+        ))
+    }
+
+    /// Bike motion at every frame, from the frames around it.
+    ///
+    /// Velocities are averaged over `smoothing` frames on each side of a frame, and
+    /// accelerations over the smoothed speeds the same way, which evens out the jitter of
+    /// single-precision positions; 0 uses only the neighbouring frames.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let motion = replay.rides[0].motion(2);
+    /// assert_eq!(motion.len(), replay.rides[0].frames.len());
+    /// assert!(motion.iter().all(|m| m.speed <= replay.rides[0].top_speed() + 1e-9));
+    /// ```
+    pub fn motion(&self, smoothing: usize) -> Vec<Motion> {
+        let dt = FRAME_TIME / 1000.;
+        let positions: Vec<Position<f64>> = self.frames.iter().map(|f| f.bike_position()).collect();
+        let angles: Vec<f64> = self.frames.iter().map(|f| f.rotation_angle()).collect();
+
+        let velocities = smooth(
+            &derivative(&positions.iter().map(|p| p.x).collect::<Vec<_>>(), dt),
+            smoothing,
+        )
+        .into_iter()
+        .zip(smooth(
+            &derivative(&positions.iter().map(|p| p.y).collect::<Vec<_>>(), dt),
+            smoothing,
+        ))
+        .map(|(x, y)| Position::new(x, y))
+        .collect::<Vec<_>>();
+        let speeds: Vec<f64> = velocities.iter().map(|v| v.x.hypot(v.y)).collect();
+        let accelerations = smooth(&derivative(&speeds, dt), smoothing);
+
+        // Rotations are unwrapped first, so a turn past 2π is not a jump back.
+        let mut unwrapped = Vec::with_capacity(angles.len());
+        for (n, &angle) in angles.iter().enumerate() {
+            let value = match unwrapped.last() {
+                Some(&last) => last + angle_difference(angles[n - 1], angle),
+                None => angle,
+            };
+            unwrapped.push(value);
+        }
+        let angular_velocities = smooth(&derivative(&unwrapped, dt), smoothing);
+
+        (0..self.frames.len())
+            .map(|n| Motion {
+                time: n as f64 * FRAME_TIME,
+                velocity: velocities[n],
+                speed: speeds[n],
+                acceleration: accelerations[n],
+                angular_velocity: angular_velocities[n],
+            })
+            .collect()
+    }
+
+    /// Length of the path of the bike, in level units.
+    pub fn distance(&self) -> f64 {
+        self.frames
+            .windows(2)
+            .map(|pair| {
+                let (a, b) = (pair[0].bike_position(), pair[1].bike_position());
+                (b.x - a.x).hypot(b.y - a.y)
+            })
+            .sum()
+    }
+
+    /// Highest speed of the bike, unsmoothed, in level units per second.
+    pub fn top_speed(&self) -> f64 {
+        self.motion(0).iter().map(|m| m.speed).fold(0., f64::max)
+    }
+
+    /// Average speed of the bike over the ride, in level units per second. Returns 0 for rides
+    /// with less than two frames.
+    pub fn average_speed(&self) -> f64 {
+        if self.frames.len() < 2 {
+            return 0.;
+        }
+        self.distance() / ((self.frames.len() - 1) as f64 * FRAME_TIME / 1000.)
+    }
+
+    /// Time of every apple taken, in order, with the object touched for it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let splits = replay.rides[0].splits();
+    /// assert_eq!(splits.len(), 2);
+    /// assert_eq!(splits[1].object, Some(2));
+    /// ```
+    pub fn splits(&self) -> Vec<Split> {
+        let mut object = None;
+        let mut splits = vec![];
+        for event in &self.events {
+            match event.event_type {
+                EventType::ObjectTouch(index) => object = Some(index),
+                EventType::Apple => {
+                    splits.push(Split {
+                        time: event.time * EVENT_TIME_SCALE,
+                        object: object.take(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        splits
+    }
+
+    /// Compares the splits of this ride with another ride of the same level. Splits are
+    /// compared in order, so the first apple of one ride is compared with the first apple of the
+    /// other, whichever apples they are.
+    pub fn compare_splits(&self, other: &Ride) -> SplitComparison {
+        let mut comparison = SplitComparison::default();
+        for (n, (a, b)) in self.splits().iter().zip(other.splits()).enumerate() {
+            comparison.deltas.push(a.time - b.time);
+            if a.object != b.object {
+                comparison.reordered.push(n);
+            }
+        }
+        comparison
+    }
+
+    /// Cuts the ride to the frames from `start` up to but not including `end`. Events from the
+    /// first kept frame up to the next frame after the last kept one are kept, and all times are
+    /// moved so the first kept frame is at zero. Frame numbers past the end of the ride are
+    /// treated as the end.
+    pub fn trim_frames(&mut self, start: usize, end: usize) {
+        let end = end.min(self.frames.len());
+        let start = start.min(end);
+        self.frames.truncate(end);
+        self.frames.drain(..start);
+
+        let from = start as f64 * FRAME_TIME / EVENT_TIME_SCALE;
+        let to = end as f64 * FRAME_TIME / EVENT_TIME_SCALE;
+        self.events.retain(|e| e.time >= from && e.time < to);
+        for event in &mut self.events {
+            event.time -= from;
+        }
+    }
+
+    /// Cuts the ride to the time window from `start` to `end`, in milliseconds. Frames are kept
+    /// from the first one at or after `start` to the last one at or before `end`, and events
+    /// between those frames; see `trim_frames`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let mut replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let ride = &mut replay.rides[0];
+    /// ride.trim(1000., 4000.);
+    /// assert_eq!(ride.frames.len(), 90);
+    /// assert!(ride.get_frame_time() <= 3000.);
+    /// ```
+    pub fn trim(&mut self, start: f64, end: f64) {
+        let (start, end) = trim_window(start, end);
+        self.trim_frames(start, end);
     }
 
     /// Gets the time based on last ObjectTouch event or 0 if the last event is not ObjectTouch.
@@ -189,7 +576,124 @@ impl Ride {
             },
             None => 0_f64,
         };
-        time * 2_289.377_289_38
+        time * EVENT_TIME_SCALE
+    }
+}
+
+// Frames from the first one at or after `start` up to and including the last one at or before
+// `end`, for times in milliseconds.
+fn trim_window(start: f64, end: f64) -> (usize, usize) {
+    let first = (start.max(0.) / FRAME_TIME).ceil();
+    let last = (end / FRAME_TIME).floor() + 1.;
+    (first as usize, last.max(first) as usize)
+}
+
+// Rate of change of evenly spaced values, `dt` apart: central differences inside and one-sided
+// at the ends.
+fn derivative(values: &[f64], dt: f64) -> Vec<f64> {
+    let n = values.len();
+    if n < 2 {
+        return vec![0.; n];
+    }
+    (0..n)
+        .map(|i| {
+            let (a, b) = (i.saturating_sub(1), (i + 1).min(n - 1));
+            (values[b] - values[a]) / ((b - a) as f64 * dt)
+        })
+        .collect()
+}
+
+// Centered moving average over `radius` values on each side, with shorter windows at the ends.
+fn smooth(values: &[f64], radius: usize) -> Vec<f64> {
+    if radius == 0 {
+        return values.to_vec();
+    }
+    let n = values.len();
+    (0..n)
+        .map(|i| {
+            let window = &values[i.saturating_sub(radius)..(i + radius + 1).min(n)];
+            window.iter().sum::<f64>() / window.len() as f64
+        })
+        .collect()
+}
+
+/// Way a replay does not fit a level, as reported by `Replay::validate_against`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayMismatch {
+    /// The replay's link is not the level's.
+    Link {
+        /// Link in the replay.
+        replay: u32,
+        /// Link of the level.
+        level: u32,
+    },
+    /// The replay was recorded on a level with another file name.
+    LevelName {
+        /// Level name in the replay.
+        replay: String,
+        /// File name of the level.
+        level: String,
+    },
+    /// An object touch event refers to an object the level does not have.
+    ObjectIndex {
+        /// Index of the ride.
+        ride: usize,
+        /// Index of the event in the ride.
+        event: usize,
+        /// Object index in the event.
+        index: i16,
+    },
+    /// More apples were taken than the level has.
+    TooManyApples {
+        /// Apple events in all rides.
+        taken: usize,
+        /// Apples in the level.
+        apples: usize,
+    },
+    /// No flower was touched after all apples were taken.
+    NotFinished,
+}
+
+/// How a replay ended, from `Replay::outcome`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    /// Touched a flower with all apples taken, at the given time.
+    Finished(Time),
+    /// Touched a killer, at the given time.
+    Killed(Time),
+    /// Ended for another reason, at the time of the last frame.
+    Stopped(Time),
+}
+
+impl Outcome {
+    /// Time the replay ended.
+    pub fn time(&self) -> Time {
+        match *self {
+            Outcome::Finished(time) | Outcome::Killed(time) | Outcome::Stopped(time) => time,
+        }
+    }
+}
+
+// Objects of a level in the order object touch events refer to them: killers, apples, flowers
+// and then the start.
+fn touch_order(level: &Level) -> Vec<&Object> {
+    let rank = |o: &Object| match o.object_type {
+        ObjectType::Killer => 0,
+        ObjectType::Apple { .. } => 1,
+        ObjectType::Exit => 2,
+        ObjectType::Player => 3,
+    };
+    let mut objects: Vec<&Object> = level.objects.iter().collect();
+    objects.sort_by_key(|o| rank(o));
+    objects
+}
+
+// Object of an object touch event.
+fn touched<'a>(objects: &[&'a Object], index: i16) -> Option<&'a Object> {
+    if index < 0 {
+        None
+    } else {
+        objects.get(index as usize).cloned()
     }
 }
 
@@ -213,6 +717,24 @@ impl Replay {
     pub fn is_multi(&self) -> bool {
         self.rides.len() > 1
     }
+
+    /// Cuts all rides to the time window from `start` to `end`, in milliseconds, keeping them in
+    /// step. See `Ride::trim`.
+    pub fn trim(&mut self, start: f64, end: f64) {
+        let (start, end) = trim_window(start, end);
+        for ride in &mut self.rides {
+            ride.trim_frames(start, end);
+        }
+    }
+
+    /// Compares the splits of the first rides of this and another replay of the same level. See
+    /// `Ride::compare_splits`.
+    pub fn compare_splits(&self, other: &Replay) -> SplitComparison {
+        match (self.rides.first(), other.rides.first()) {
+            (Some(a), Some(b)) => a.compare_splits(b),
+            _ => SplitComparison::default(),
+        }
+    }
 }
 
 impl Default for Replay {
@@ -230,22 +752,22 @@ named!(headerandride<(ReplayHeader, Ride)>,
     flag_tag: boolean >>
     link: le_u32 >>
     level: apply!(null_padded_string, 16) >>
-    bodyx: many_m_n!(frame_count, frame_count, le_f32) >>
-    bodyy: many_m_n!(frame_count, frame_count, le_f32) >>
-    leftwheelx: many_m_n!(frame_count, frame_count, le_i16) >>
-    leftwheely: many_m_n!(frame_count, frame_count, le_i16) >>
-    rightwheelx: many_m_n!(frame_count, frame_count, le_i16) >>
-    rightwheely: many_m_n!(frame_count, frame_count, le_i16) >>
-    headx: many_m_n!(frame_count, frame_count, le_i16) >>
-    heady: many_m_n!(frame_count, frame_count, le_i16) >>
-    rotation: many_m_n!(frame_count, frame_count, le_i16) >>
-    leftwheelrotation: many_m_n!(frame_count, frame_count, le_u8) >>
-    rightwheelrotation: many_m_n!(frame_count, frame_count, le_u8) >>
-    dir_and_throttle: many_m_n!(frame_count, frame_count, le_u8) >>
-    back_wheel: many_m_n!(frame_count, frame_count, le_u8) >>
-    collision_strength: many_m_n!(frame_count, frame_count, le_u8) >>
+    bodyx: count!(le_f32, frame_count) >>
+    bodyy: count!(le_f32, frame_count) >>
+    leftwheelx: count!(le_i16, frame_count) >>
+    leftwheely: count!(le_i16, frame_count) >>
+    rightwheelx: count!(le_i16, frame_count) >>
+    rightwheely: count!(le_i16, frame_count) >>
+    headx: count!(le_i16, frame_count) >>
+    heady: count!(le_i16, frame_count) >>
+    rotation: count!(le_i16, frame_count) >>
+    leftwheelrotation: count!(le_u8, frame_count) >>
+    rightwheelrotation: count!(le_u8, frame_count) >>
+    dir_and_throttle: count!(le_u8, frame_count) >>
+    back_wheel: count!(le_u8, frame_count) >>
+    collision_strength: count!(le_u8, frame_count) >>
     num_events: map!(le_i32, |x| x as usize) >>
-    events: many_m_n!(num_events, num_events, event) >>
+    events: count!(event, num_events) >>
     verify!(le_i32, |x| x == END_OF_PLAYER) >>
     (ReplayHeader {
          multi,
@@ -391,33 +913,206 @@ impl Replay {
         }
     }
 
+    /// Reads a replay from a reader.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let file = std::fs::File::open("tests/assets/replays/test_1.rec").unwrap();
+    /// let rec = Replay::read_from(file).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ElmaError> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        Replay::parse_replay(&buffer)
+    }
+
     /// Returns replay data as a buffer of bytes.
     pub fn to_bytes(&self) -> Result<Vec<u8>, ElmaError> {
         let mut bytes: Vec<u8> = vec![];
+        self.write_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Writes the replay in its binary form to a writer.
+    ///
+    /// The replay is written piece by piece as it is encoded, with several small writes for each
+    /// frame, so wrap unbuffered writers such as files in a `BufWriter`. A level name that cannot
+    /// be encoded fails after part of the replay has been written; use `to_bytes` to encode all
+    /// of it before writing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::rec::*;
+    /// let rec = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let mut buffer = vec![];
+    /// rec.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, rec.to_bytes().unwrap());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), ElmaError> {
         for r in &self.rides {
             // Number of frames.
-            bytes.write_i32::<LE>(r.frames.len() as i32)?;
+            writer.write_i32::<LE>(r.frames.len() as i32)?;
             // Replay version.
-            bytes.write_u32::<LE>(REPLAY_VERSION)?;
+            writer.write_u32::<LE>(REPLAY_VERSION)?;
             // Multi-player replay or not.
-            bytes.write_i32::<LE>(if self.is_multi() { 1_i32 } else { 0_i32 })?;
+            writer.write_i32::<LE>(if self.is_multi() { 1_i32 } else { 0_i32 })?;
             // Flag-tag replay or not.
-            bytes.write_i32::<LE>(if self.flag_tag { 1_i32 } else { 0_i32 })?;
+            writer.write_i32::<LE>(if self.flag_tag { 1_i32 } else { 0_i32 })?;
             // Link.
-            bytes.write_u32::<LE>(self.link)?;
+            writer.write_u32::<LE>(self.link)?;
             // Level name.
-            bytes.extend_from_slice(&string_null_pad(&self.level, 12)?);
+            writer.write_all(&string_null_pad(&self.level, 12)?)?;
             // Garbage value.
-            bytes.write_i32::<LE>(0x00_i32)?;
+            writer.write_i32::<LE>(0x00_i32)?;
 
             // Frames and events.
-            bytes.extend_from_slice(&write_frames(&r.frames)?);
-            bytes.extend_from_slice(&write_events(&r.events)?);
+            write_frames(&r.frames, &mut writer)?;
+            write_events(&r.events, &mut writer)?;
 
             // End of player marker.
-            bytes.write_i32::<LE>(END_OF_PLAYER)?;
+            writer.write_i32::<LE>(END_OF_PLAYER)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the replay could have been recorded on the level, and returns every
+    /// mismatch found.
+    ///
+    /// The link must be the level's, and the level name must match the file name in the level's
+    /// `path` without regard to case; levels without a path skip the name check. Object touch
+    /// events index the level's objects sorted as killers, apples, flowers and start. Apples
+    /// taken in all rides together may not be more than the level has, and a flower must be
+    /// touched once all apples are taken.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// # use elma::rec::*;
+    /// let replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// let level = Level::new();
+    /// let errors = replay.validate_against(&level).unwrap_err();
+    /// assert_eq!(
+    ///     errors[0],
+    ///     ReplayMismatch::Link {
+    ///         replay: replay.link,
+    ///         level: level.link,
+    ///     }
+    /// );
+    /// ```
+    pub fn validate_against(&self, level: &Level) -> Result<(), Vec<ReplayMismatch>> {
+        let mut errors = vec![];
+        if self.link != level.link {
+            errors.push(ReplayMismatch::Link {
+                replay: self.link,
+                level: level.link,
+            });
+        }
+        if let Some(name) = level.path.as_ref().and_then(|path| path.file_name()) {
+            let name = name.to_string_lossy();
+            if !self.level.eq_ignore_ascii_case(&name) {
+                errors.push(ReplayMismatch::LevelName {
+                    replay: self.level.clone(),
+                    level: name.into_owned(),
+                });
+            }
+        }
+
+        let objects = touch_order(level);
+        let apples = objects.iter().filter(|o| o.is_apple()).count();
+
+        let mut taken = 0;
+        let mut finished = false;
+        for (ride, event, e) in self.timeline() {
+            match e.event_type {
+                EventType::ObjectTouch(index) => match touched(&objects, index) {
+                    None => errors.push(ReplayMismatch::ObjectIndex { ride, event, index }),
+                    Some(object) => {
+                        if taken >= apples && object.object_type == ObjectType::Exit {
+                            finished = true;
+                        }
+                    }
+                },
+                EventType::Apple => taken += 1,
+                _ => {}
+            }
+        }
+        if taken > apples {
+            errors.push(ReplayMismatch::TooManyApples { taken, apples });
+        }
+        if !finished {
+            errors.push(ReplayMismatch::NotFinished);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
-        Ok(bytes)
+    }
+
+    /// How the replay ended on the level, with the time it ended.
+    ///
+    /// Object touch events are resolved to the level's objects, in all rides together. The run
+    /// is finished at the first flower touched once all apples are taken, and killed at the
+    /// first killer touched before that. Other runs, such as ones ended by the head hitting the
+    /// ground or by quitting, stop at the time of the last frame. Times are rounded to
+    /// milliseconds and then cut to hundredths, as in `get_time_hs`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// # use elma::rec::*;
+    /// # use elma::Time;
+    /// let replay = Replay::load("tests/assets/replays/test_1.rec").unwrap();
+    /// // The replay takes two apples and ends at the fourth object.
+    /// let mut level = Level::new();
+    /// for object_type in vec![ObjectType::Killer, ObjectType::default(), ObjectType::default()] {
+    ///     level.objects.push(Object { object_type, ..Object::new() });
+    /// }
+    /// assert_eq!(replay.outcome(&level), Outcome::Finished(Time(1464)));
+    /// ```
+    pub fn outcome(&self, level: &Level) -> Outcome {
+        let objects = touch_order(level);
+        let apples = objects.iter().filter(|o| o.is_apple()).count();
+        let hundredths = |ms: f64| Time(ms.round() as i32 / 10);
+
+        let mut taken = 0;
+        for (_, _, e) in self.timeline() {
+            match e.event_type {
+                EventType::ObjectTouch(index) => match touched(&objects, index) {
+                    Some(object) if object.object_type == ObjectType::Killer => {
+                        return Outcome::Killed(hundredths(e.time * EVENT_TIME_SCALE));
+                    }
+                    Some(object) if object.object_type == ObjectType::Exit && taken >= apples => {
+                        return Outcome::Finished(hundredths(e.time * EVENT_TIME_SCALE));
+                    }
+                    _ => {}
+                },
+                EventType::Apple => taken += 1,
+                _ => {}
+            }
+        }
+        let frame_time = self
+            .rides
+            .iter()
+            .map(|r| r.get_frame_time())
+            .fold(0., f64::max);
+        Outcome::Stopped(hundredths(frame_time))
+    }
+
+    // Events of all rides in time order, with their ride and index.
+    fn timeline(&self) -> Vec<(usize, usize, &Event)> {
+        let mut events = vec![];
+        for (r, ride) in self.rides.iter().enumerate() {
+            events.extend(ride.events.iter().enumerate().map(|(n, e)| (r, n, e)));
+        }
+        events.sort_by(|a, b| a.2.time.total_cmp(&b.2.time));
+        events
     }
 
     /// Save replay as a file.
@@ -430,7 +1125,8 @@ impl Replay {
 
     /// Get time of replay. Returns tuple with milliseconds and whether replay was finished,
     /// caveat being that there is no way to tell if a replay was finished or not just from the
-    /// replay file with a 100% certainty. Merely provided for convenience.
+    /// replay file with a 100% certainty. Merely provided for convenience; `outcome` tells for
+    /// sure given the level.
     /// # Examples
     ///
     /// ```rust
@@ -484,99 +1180,145 @@ impl Replay {
     }
 }
 
-/// Function for writing frame data.
-fn write_frames(frame_data: &[Frame]) -> Result<Vec<u8>, ElmaError> {
-    let mut bytes = vec![];
+/// What `relink_replays` did with a replay.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelinkStatus {
+    /// The replay's link was changed to the level's.
+    Updated,
+    /// The replay already had the level's link.
+    AlreadyLinked,
+    /// Skipped, as the replay was recorded on a level with another name, given here.
+    OtherLevel(String),
+    /// Skipped, as the replay was recorded on another version of the level, with the replay's
+    /// link.
+    OtherLink(u32),
+}
 
-    let mut bike_x = vec![];
-    let mut bike_y = vec![];
-    let mut left_x = vec![];
-    let mut left_y = vec![];
-    let mut right_x = vec![];
-    let mut right_y = vec![];
-    let mut head_x = vec![];
-    let mut head_y = vec![];
-    let mut rotation = vec![];
-    let mut left_rotation = vec![];
-    let mut right_rotation = vec![];
-    let mut data = vec![];
-    let mut back_wheel = vec![];
-    let mut collision = vec![];
+/// Relinks replays to a level whose link has changed, such as after `Level::generate_link`.
+///
+/// Replays recorded on the level with `old_link` get the level's current link. Level names are
+/// compared without case against the file name in the level's `path`; a level without a path
+/// matches replays of any level. Returns what was done with each replay, in order.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::lev::*;
+/// # use elma::rec::*;
+/// let mut replays = vec![Replay::load("tests/assets/replays/test_1.rec").unwrap()];
+/// let old_link = replays[0].link;
+/// let mut level = Level::new();
+/// level.path = Some("levels/TUTOR14.LEV".into());
+/// let status = relink_replays(&level, old_link, &mut replays);
+/// assert_eq!(status, vec![RelinkStatus::Updated]);
+/// assert_eq!(replays[0].link, level.link);
+/// ```
+pub fn relink_replays(level: &Level, old_link: u32, replays: &mut [Replay]) -> Vec<RelinkStatus> {
+    let name = level
+        .path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned());
+    replays
+        .iter_mut()
+        .map(|replay| {
+            if let Some(ref name) = name {
+                if !replay.level.eq_ignore_ascii_case(name) {
+                    return RelinkStatus::OtherLevel(replay.level.clone());
+                }
+            }
+            if replay.link == level.link {
+                RelinkStatus::AlreadyLinked
+            } else if replay.link == old_link {
+                replay.link = level.link;
+                RelinkStatus::Updated
+            } else {
+                RelinkStatus::OtherLink(replay.link)
+            }
+        })
+        .collect()
+}
 
+/// Function for writing frame data. Frame fields are stored column by column, with one small
+/// write for each field of each frame.
+fn write_frames<W: Write>(frame_data: &[Frame], writer: &mut W) -> Result<(), ElmaError> {
     for frame in frame_data {
-        bike_x.write_f32::<LE>(frame.bike.x)?;
-        bike_y.write_f32::<LE>(frame.bike.y)?;
-
-        left_x.write_i16::<LE>(frame.left_wheel.x)?;
-        left_y.write_i16::<LE>(frame.left_wheel.y)?;
-
-        right_x.write_i16::<LE>(frame.right_wheel.x)?;
-        right_y.write_i16::<LE>(frame.right_wheel.y)?;
-
-        head_x.write_i16::<LE>(frame.head.x)?;
-        head_y.write_i16::<LE>(frame.head.y)?;
-
-        rotation.write_i16::<LE>(frame.rotation)?;
-        left_rotation.write_u8(frame.left_wheel_rotation)?;
-        right_rotation.write_u8(frame.right_wheel_rotation)?;
-
-        data.write_u8(frame.throttle_and_dir)?;
-
-        back_wheel.write_u8(frame.back_wheel_rot_speed)?;
-        collision.write_u8(frame.collision_strength)?;
+        writer.write_f32::<LE>(frame.bike.x)?;
+    }
+    for frame in frame_data {
+        writer.write_f32::<LE>(frame.bike.y)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.left_wheel.x)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.left_wheel.y)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.right_wheel.x)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.right_wheel.y)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.head.x)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.head.y)?;
+    }
+    for frame in frame_data {
+        writer.write_i16::<LE>(frame.rotation)?;
+    }
+    for frame in frame_data {
+        writer.write_u8(frame.left_wheel_rotation)?;
+    }
+    for frame in frame_data {
+        writer.write_u8(frame.right_wheel_rotation)?;
+    }
+    for frame in frame_data {
+        writer.write_u8(frame.throttle_and_dir)?;
+    }
+    for frame in frame_data {
+        writer.write_u8(frame.back_wheel_rot_speed)?;
+    }
+    for frame in frame_data {
+        writer.write_u8(frame.collision_strength)?;
     }
 
-    bytes.extend_from_slice(&bike_x);
-    bytes.extend_from_slice(&bike_y);
-    bytes.extend_from_slice(&left_x);
-    bytes.extend_from_slice(&left_y);
-    bytes.extend_from_slice(&right_x);
-    bytes.extend_from_slice(&right_y);
-    bytes.extend_from_slice(&head_x);
-    bytes.extend_from_slice(&head_y);
-    bytes.extend_from_slice(&rotation);
-    bytes.extend_from_slice(&left_rotation);
-    bytes.extend_from_slice(&right_rotation);
-    bytes.extend_from_slice(&data);
-    bytes.extend_from_slice(&back_wheel);
-    bytes.extend_from_slice(&collision);
-
-    Ok(bytes)
+    Ok(())
 }
 
 /// Function for writing event data.
-fn write_events(event_data: &[Event]) -> Result<Vec<u8>, ElmaError> {
-    let mut bytes = vec![];
-
+fn write_events<W: Write>(event_data: &[Event], writer: &mut W) -> Result<(), ElmaError> {
     // Number of events.
-    bytes.write_i32::<LE>(event_data.len() as i32)?;
+    writer.write_i32::<LE>(event_data.len() as i32)?;
 
     for event in event_data {
-        bytes.write_f64::<LE>(event.time)?;
+        writer.write_f64::<LE>(event.time)?;
         let default_info = -1;
         let default_info2 = 0.99;
         let event_type = (&event.event_type).into();
         match event.event_type {
             EventType::ObjectTouch(info) => {
-                bytes.write_i16::<LE>(info)?;
-                bytes.write_u8(event_type)?;
-                bytes.write_u8(0)?;
-                bytes.write_f32::<LE>(0.0)?; // always 0 for an ObjectTouch event
+                writer.write_i16::<LE>(info)?;
+                writer.write_u8(event_type)?;
+                writer.write_u8(0)?;
+                writer.write_f32::<LE>(0.0)?; // always 0 for an ObjectTouch event
             }
             EventType::Ground(info2) => {
-                bytes.write_i16::<LE>(default_info)?;
-                bytes.write_u8(event_type)?;
-                bytes.write_u8(0)?;
-                bytes.write_f32::<LE>(info2)?;
+                writer.write_i16::<LE>(default_info)?;
+                writer.write_u8(event_type)?;
+                writer.write_u8(0)?;
+                writer.write_f32::<LE>(info2)?;
             }
             _ => {
-                bytes.write_i16::<LE>(default_info)?;
-                bytes.write_u8(event_type)?;
-                bytes.write_u8(0)?;
-                bytes.write_f32::<LE>(default_info2)?;
+                writer.write_i16::<LE>(default_info)?;
+                writer.write_u8(event_type)?;
+                writer.write_u8(0)?;
+                writer.write_f32::<LE>(default_info2)?;
             }
         }
     }
 
-    Ok(bytes)
+    Ok(())
 }
@@ -0,0 +1,301 @@
+use super::{
+    geometry::{cross, distance, union},
+    lev::{Level, Polygon},
+    Position,
+};
+
+/// Options for `repair_polygons`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepairOptions {
+    /// Vertices closer than this to the previous vertex, or to the line between their
+    /// neighbours, are removed. In level units.
+    pub tolerance: f64,
+    /// Replace self-intersecting ground polygons with simple polygons covering the same area.
+    pub split_self_intersections: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        RepairOptions {
+            tolerance: 1e-6,
+            split_self_intersections: false,
+        }
+    }
+}
+
+impl RepairOptions {
+    /// Creates new repair options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Change made by `repair_polygons`. Polygon and vertex indices refer to the level as it was
+/// before the repair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// Vertex at the same place as the previous one, making a zero-length edge.
+    RemovedDuplicateVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Vertex index.
+        vertex: usize,
+    },
+    /// Ground vertex on the line through its neighbours, either in between them or as the tip
+    /// of a zero-width spike.
+    RemovedCollinearVertex {
+        /// Polygon index.
+        polygon: usize,
+        /// Vertex index.
+        vertex: usize,
+    },
+    /// Polygon left with fewer than 3 vertices.
+    RemovedPolygon {
+        /// Polygon index.
+        polygon: usize,
+    },
+    /// Two identical ground polygons, which cancel each other out.
+    RemovedDuplicatePolygons {
+        /// Index of the first polygon.
+        first: usize,
+        /// Index of the second polygon.
+        second: usize,
+    },
+    /// Self-intersecting ground polygon replaced by simple polygons.
+    SplitPolygon {
+        /// Polygon index.
+        polygon: usize,
+        /// Number of polygons it was replaced with.
+        parts: usize,
+    },
+}
+
+/// Cleans up the polygons of a level and returns the changes made.
+///
+/// Duplicate consecutive vertices are removed from all polygons. Ground polygons also lose
+/// vertices lying on the line between their neighbours; grass polygons keep them, since
+/// merging edges could change which edge is the longest and therefore not drawn. Polygons with
+/// fewer than 3 vertices left are removed, as are pairs of identical ground polygons. With
+/// `split_self_intersections`, ground polygons crossing themselves are replaced by simple
+/// polygons describing the same ground. The remaining polygons keep their order.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::lev::*;
+/// # use elma::repair::*;
+/// # use elma::Position;
+/// let mut level = Level::new();
+/// level.polygons[0].vertices.insert(1, Position::new(10., 0.));
+/// let repairs = repair_polygons(&mut level, &RepairOptions::new());
+/// assert_eq!(
+///     repairs,
+///     vec![Repair::RemovedDuplicateVertex {
+///         polygon: 0,
+///         vertex: 1
+///     }]
+/// );
+/// assert_eq!(level.polygons, Level::new().polygons);
+/// ```
+pub fn repair_polygons(level: &mut Level, options: &RepairOptions) -> Vec<Repair> {
+    let mut repairs = vec![];
+    // Repaired polygons with the index they had before.
+    let mut polygons: Vec<(usize, Polygon)> = vec![];
+
+    for (index, polygon) in level.polygons.iter().enumerate() {
+        let mut vertices: Vec<(usize, Position<f64>)> =
+            polygon.vertices.iter().cloned().enumerate().collect();
+        remove_duplicates(&mut vertices, options.tolerance, &mut |vertex| {
+            repairs.push(Repair::RemovedDuplicateVertex {
+                polygon: index,
+                vertex,
+            })
+        });
+        if !polygon.grass {
+            remove_collinear(&mut vertices, options.tolerance, &mut |vertex| {
+                repairs.push(Repair::RemovedCollinearVertex {
+                    polygon: index,
+                    vertex,
+                })
+            });
+        }
+        if vertices.len() < 3 {
+            repairs.push(Repair::RemovedPolygon { polygon: index });
+            continue;
+        }
+
+        let repaired = Polygon {
+            grass: polygon.grass,
+            vertices: vertices.into_iter().map(|(_, v)| v).collect(),
+        };
+        if options.split_self_intersections && !repaired.grass && self_intersects(&repaired) {
+            let parts = union(&[repaired], &[]);
+            repairs.push(Repair::SplitPolygon {
+                polygon: index,
+                parts: parts.len(),
+            });
+            polygons.extend(parts.into_iter().map(|part| (index, part)));
+        } else {
+            polygons.push((index, repaired));
+        }
+    }
+
+    // Identical ground polygons cancel out under the even-odd rule.
+    let mut removed = vec![false; polygons.len()];
+    for first in 0..polygons.len() {
+        if removed[first] || polygons[first].1.grass {
+            continue;
+        }
+        let second = (first + 1..polygons.len()).find(|&second| {
+            !removed[second]
+                && !polygons[second].1.grass
+                && polygons[first].0 != polygons[second].0
+                && same_ring(&polygons[first].1.vertices, &polygons[second].1.vertices)
+        });
+        if let Some(second) = second {
+            removed[first] = true;
+            removed[second] = true;
+            repairs.push(Repair::RemovedDuplicatePolygons {
+                first: polygons[first].0,
+                second: polygons[second].0,
+            });
+        }
+    }
+
+    level.polygons = polygons
+        .into_iter()
+        .zip(removed)
+        .filter(|&(_, removed)| !removed)
+        .map(|((_, polygon), _)| polygon)
+        .collect();
+    repairs
+}
+
+/// Returns whether any two edges of the polygon touch or cross, other than neighbouring edges
+/// sharing their vertex.
+pub fn self_intersects(polygon: &Polygon) -> bool {
+    let vertices = &polygon.vertices;
+    let n = vertices.len();
+    for i in 0..n {
+        let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+        for j in i + 1..n {
+            let (c, d) = (vertices[j], vertices[(j + 1) % n]);
+            if j == i + 1 || (i == 0 && j == n - 1) {
+                // Neighbours only meet at their shared vertex, unless they fold back.
+                let (shared, other_a, other_b) = if j == i + 1 { (b, a, d) } else { (a, b, c) };
+                if folds_back(&shared, &other_a, &other_b) {
+                    return true;
+                }
+            } else if segments_touch(&a, &b, &c, &d) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn remove_duplicates(
+    vertices: &mut Vec<(usize, Position<f64>)>,
+    tolerance: f64,
+    report: &mut dyn FnMut(usize),
+) {
+    let mut n = 1;
+    while n < vertices.len() {
+        if distance(&vertices[n - 1].1, &vertices[n].1) <= tolerance {
+            report(vertices.remove(n).0);
+        } else {
+            n += 1;
+        }
+    }
+    // The last vertex connects back to the first.
+    while vertices.len() > 1
+        && distance(&vertices[vertices.len() - 1].1, &vertices[0].1) <= tolerance
+    {
+        report(vertices.pop().unwrap().0);
+    }
+}
+
+fn remove_collinear(
+    vertices: &mut Vec<(usize, Position<f64>)>,
+    tolerance: f64,
+    report: &mut dyn FnMut(usize),
+) {
+    let mut changed = true;
+    while changed && vertices.len() > 2 {
+        changed = false;
+        let mut n = 0;
+        while vertices.len() > 2 && n < vertices.len() {
+            let len = vertices.len();
+            let prev = vertices[(n + len - 1) % len].1;
+            let current = vertices[n].1;
+            let next = vertices[(n + 1) % len].1;
+            let span = distance(&prev, &next);
+            // When the neighbours coincide, the vertex is the tip of a spike.
+            let offset = if span == 0. {
+                0.
+            } else {
+                cross(
+                    current.x - prev.x,
+                    current.y - prev.y,
+                    next.x - prev.x,
+                    next.y - prev.y,
+                )
+                .abs()
+                    / span
+            };
+            if offset <= tolerance {
+                report(vertices.remove(n).0);
+                changed = true;
+                // Removing a spike tip can leave its base vertices on top of each other.
+                remove_duplicates(vertices, tolerance, report);
+            } else {
+                n += 1;
+            }
+        }
+    }
+}
+
+fn orientation(a: &Position<f64>, b: &Position<f64>, c: &Position<f64>) -> f64 {
+    cross(b.x - a.x, b.y - a.y, c.x - a.x, c.y - a.y)
+}
+
+// Whether `p`, known to be on the line through a-b, lies within the segment.
+fn within(a: &Position<f64>, b: &Position<f64>, p: &Position<f64>) -> bool {
+    p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x) && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+}
+
+fn segments_touch(
+    a: &Position<f64>,
+    b: &Position<f64>,
+    c: &Position<f64>,
+    d: &Position<f64>,
+) -> bool {
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+    if ((o1 > 0. && o2 < 0.) || (o1 < 0. && o2 > 0.))
+        && ((o3 > 0. && o4 < 0.) || (o3 < 0. && o4 > 0.))
+    {
+        return true;
+    }
+    (o1 == 0. && within(a, b, c))
+        || (o2 == 0. && within(a, b, d))
+        || (o3 == 0. && within(c, d, a))
+        || (o4 == 0. && within(c, d, b))
+}
+
+// Whether two edges leaving `shared` overlap.
+fn folds_back(shared: &Position<f64>, a: &Position<f64>, b: &Position<f64>) -> bool {
+    orientation(shared, a, b) == 0.
+        && (a.x - shared.x) * (b.x - shared.x) + (a.y - shared.y) * (b.y - shared.y) > 0.
+}
+
+// Whether two rings have the same vertices in the same cyclic order, in either direction.
+fn same_ring(a: &[Position<f64>], b: &[Position<f64>]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let n = a.len();
+    (0..n).filter(|&start| b[start] == a[0]).any(|start| {
+        (0..n).all(|i| a[i] == b[(start + i) % n]) || (0..n).all(|i| a[i] == b[(start + n - i) % n])
+    })
+}
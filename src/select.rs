@@ -0,0 +1,274 @@
+use super::{
+    constants::{MAX_OBJECTS, MAX_PICTURES, MAX_POLYGONS},
+    geometry::{area, cross, crosses_ray, intersection},
+    lev::{Level, Object, ObjectType, Picture, Polygon, TopologyError},
+    Position,
+};
+use std::slice;
+
+/// Area of a level to select from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Region {
+    /// Rectangle between two opposite corners, edges included.
+    Rect(Position<f64>, Position<f64>),
+    /// Closed freehand outline. Points are inside when the outline goes around them an odd
+    /// number of times.
+    Lasso(Vec<Position<f64>>),
+}
+
+impl Region {
+    /// Returns whether a point is inside the region.
+    pub fn contains(&self, point: &Position<f64>) -> bool {
+        match *self {
+            Region::Rect(a, b) => {
+                point.x >= a.x.min(b.x)
+                    && point.x <= a.x.max(b.x)
+                    && point.y >= a.y.min(b.y)
+                    && point.y <= a.y.max(b.y)
+            }
+            Region::Lasso(ref outline) => {
+                let n = outline.len();
+                (0..n).fold(false, |inside, i| {
+                    inside != crosses_ray(&outline[i], &outline[(i + 1) % n], point)
+                })
+            }
+        }
+    }
+
+    /// Outline of the region as a ground polygon.
+    pub fn to_polygon(&self) -> Polygon {
+        let vertices = match *self {
+            Region::Rect(a, b) => vec![
+                Position::new(a.x, a.y),
+                Position::new(b.x, a.y),
+                Position::new(b.x, b.y),
+                Position::new(a.x, b.y),
+            ],
+            Region::Lasso(ref outline) => outline.clone(),
+        };
+        Polygon {
+            grass: false,
+            vertices,
+        }
+    }
+
+    // Whether the polygon is entirely inside the region.
+    fn surrounds(&self, polygon: &Polygon) -> bool {
+        if !polygon.vertices.iter().all(|v| self.contains(v)) {
+            return false;
+        }
+        match *self {
+            // Rectangles are convex, so the edges are inside as well.
+            Region::Rect(..) => true,
+            Region::Lasso(ref outline) => !edges(&polygon.vertices)
+                .any(|(a, b)| edges(outline).any(|(c, d)| segments_cross(a, b, c, d))),
+        }
+    }
+}
+
+fn edges(ring: &[Position<f64>]) -> impl Iterator<Item = (&Position<f64>, &Position<f64>)> {
+    let n = ring.len();
+    (0..n).map(move |i| (&ring[i], &ring[(i + 1) % n]))
+}
+
+// Whether the segments cross at a point inside both of them.
+fn segments_cross(
+    a: &Position<f64>,
+    b: &Position<f64>,
+    c: &Position<f64>,
+    d: &Position<f64>,
+) -> bool {
+    let side = |p: &Position<f64>, q: &Position<f64>, r: &Position<f64>| {
+        cross(q.x - p.x, q.y - p.y, r.x - p.x, r.y - p.y)
+    };
+    side(a, b, c) * side(a, b, d) < 0. && side(c, d, a) * side(c, d, b) < 0.
+}
+
+/// Indices of selected polygons, objects and pictures of a level.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Selection {
+    /// Selected polygons.
+    pub polygons: Vec<usize>,
+    /// Selected objects.
+    pub objects: Vec<usize>,
+    /// Selected pictures.
+    pub pictures: Vec<usize>,
+}
+
+impl Selection {
+    /// Creates an empty selection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty() && self.objects.is_empty() && self.pictures.is_empty()
+    }
+}
+
+/// Selects the polygons entirely inside the region, and the objects and pictures whose position
+/// is inside it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::lev::*;
+/// # use elma::select::*;
+/// # use elma::Position;
+/// let level = Level::new();
+/// let selection = select(&level, &Region::Rect(Position::new(0., 0.), Position::new(5., 5.)));
+/// assert!(selection.polygons.is_empty());
+/// assert_eq!(selection.objects, vec![0]);
+/// ```
+pub fn select(level: &Level, region: &Region) -> Selection {
+    Selection {
+        polygons: (0..level.polygons.len())
+            .filter(|&n| region.surrounds(&level.polygons[n]))
+            .collect(),
+        objects: (0..level.objects.len())
+            .filter(|&n| region.contains(&level.objects[n].position))
+            .collect(),
+        pictures: (0..level.pictures.len())
+            .filter(|&n| region.contains(&level.pictures[n].position))
+            .collect(),
+    }
+}
+
+/// Polygons, objects and pictures copied out of a level, in level coordinates.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Fragment {
+    /// Polygons.
+    pub polygons: Vec<Polygon>,
+    /// Objects.
+    pub objects: Vec<Object>,
+    /// Pictures.
+    pub pictures: Vec<Picture>,
+}
+
+impl Fragment {
+    /// Creates an empty fragment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copies the selected polygons, objects and pictures. Indices that are not in the level are
+    /// skipped.
+    pub fn from_selection(level: &Level, selection: &Selection) -> Self {
+        Fragment {
+            polygons: selection
+                .polygons
+                .iter()
+                .filter_map(|&n| level.polygons.get(n).cloned())
+                .collect(),
+            objects: selection
+                .objects
+                .iter()
+                .filter_map(|&n| level.objects.get(n).cloned())
+                .collect(),
+            pictures: selection
+                .pictures
+                .iter()
+                .filter_map(|&n| level.pictures.get(n).cloned())
+                .collect(),
+        }
+    }
+
+    /// Pastes the fragment into the level, moved by `offset`, and returns the indices of the
+    /// pasted items in the level.
+    ///
+    /// Items are added after the level's own. A level has exactly one start, so if both the
+    /// fragment and the level have one, the level's start is moved to the pasted one's position
+    /// instead of adding another. Nothing is pasted when the fragment has more than one start,
+    /// or when the level would go over the polygon, object or picture limits.
+    pub fn paste(
+        &self,
+        level: &mut Level,
+        offset: Position<f64>,
+    ) -> Result<Selection, TopologyError> {
+        let is_player = |o: &&Object| o.object_type == ObjectType::Player;
+        let players = self.objects.iter().filter(is_player).count();
+        if players > 1 {
+            return Err(TopologyError::InvalidPlayerCount(players));
+        }
+        let level_player = level.objects.iter().position(|o| is_player(&o));
+        let replaced = if players == 1 { level_player } else { None };
+
+        let polygons = level.polygons.len() + self.polygons.len();
+        if polygons > MAX_POLYGONS {
+            return Err(TopologyError::MaxPolygons(polygons - MAX_POLYGONS));
+        }
+        let objects = level.objects.len() + self.objects.len() - replaced.map_or(0, |_| 1);
+        if objects > MAX_OBJECTS {
+            return Err(TopologyError::MaxObjects(objects - MAX_OBJECTS));
+        }
+        let pictures = level.pictures.len() + self.pictures.len();
+        if pictures > MAX_PICTURES {
+            return Err(TopologyError::MaxPictures(pictures - MAX_PICTURES));
+        }
+
+        let moved = |p: Position<f64>| Position::new(p.x + offset.x, p.y + offset.y);
+        let mut pasted = Selection::new();
+        for polygon in &self.polygons {
+            pasted.polygons.push(level.polygons.len());
+            level.polygons.push(Polygon {
+                grass: polygon.grass,
+                vertices: polygon.vertices.iter().map(|&v| moved(v)).collect(),
+            });
+        }
+        for object in &self.objects {
+            match replaced {
+                Some(index) if object.object_type == ObjectType::Player => {
+                    level.objects[index].position = moved(object.position);
+                    pasted.objects.push(index);
+                }
+                _ => {
+                    pasted.objects.push(level.objects.len());
+                    level.objects.push(Object {
+                        position: moved(object.position),
+                        object_type: object.object_type.clone(),
+                    });
+                }
+            }
+        }
+        for picture in &self.pictures {
+            pasted.pictures.push(level.pictures.len());
+            level.pictures.push(Picture {
+                position: moved(picture.position),
+                ..picture.clone()
+            });
+        }
+        Ok(pasted)
+    }
+}
+
+/// Copies the part of the level inside the region.
+///
+/// Without clipping, this copies what `select` selects. With clipping, ground polygons reaching
+/// into the region are cut along its outline and only the parts inside are kept, while grass
+/// polygons are still copied only when they are entirely inside.
+pub fn extract(level: &Level, region: &Region, clip: bool) -> Fragment {
+    let mut fragment = Fragment::from_selection(level, &select(level, region));
+    if clip {
+        let outline = [region.to_polygon()];
+        fragment.polygons = level
+            .polygons
+            .iter()
+            .flat_map(|polygon| {
+                if polygon.grass {
+                    if region.surrounds(polygon) {
+                        vec![polygon.clone()]
+                    } else {
+                        vec![]
+                    }
+                } else {
+                    intersection(slice::from_ref(polygon), &outline)
+                        .into_iter()
+                        .filter(|p| area(&p.vertices) != 0.)
+                        .collect()
+                }
+            })
+            .collect();
+    }
+    fragment
+}
@@ -174,7 +174,7 @@ impl fmt::Display for Time {
 /// ```
 /// let vertex = elma::Position::new(23.1928_f64, -199.200019_f64);
 /// ```
-#[derive(Debug, Default, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct Position<T> {
     /// X-position.
     pub x: T,
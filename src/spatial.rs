@@ -0,0 +1,437 @@
+use super::{
+    constants::OBJECT_RADIUS,
+    geometry::{cross, distance},
+    lev::{Level, Object, Polygon},
+    Position,
+};
+
+// Most grid cells along one side.
+const MAX_CELLS: usize = 1024;
+
+/// Ground polygon edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Edge {
+    /// Index of the polygon in the level.
+    pub polygon: usize,
+    /// Index of the vertex the edge starts at. The edge ends at the next vertex.
+    pub vertex: usize,
+    /// Start of the edge.
+    pub from: Position<f64>,
+    /// End of the edge.
+    pub to: Position<f64>,
+}
+
+/// Result of a nearest edge or raycast query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeHit {
+    /// The edge found.
+    pub edge: Edge,
+    /// Point on the edge.
+    pub point: Position<f64>,
+    /// Distance from the query point or ray origin to `point`.
+    pub distance: f64,
+}
+
+/// Uniform grid over the ground edges of a level, for fast nearest edge, rectangle and
+/// raycast queries. Grass polygons are not indexed.
+///
+/// The index is a snapshot; rebuild it after changing the polygons.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::lev::*;
+/// # use elma::spatial::*;
+/// # use elma::Position;
+/// let level = Level::new();
+/// let index = SpatialIndex::new(&level);
+/// let hit = index
+///     .raycast(Position::new(5., 3.), Position::new(0., -1.), 100.)
+///     .unwrap();
+/// assert_eq!(hit.point, Position::new(5., 0.));
+/// assert_eq!(hit.distance, 3.);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpatialIndex {
+    edges: Vec<Edge>,
+    origin: Position<f64>,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialIndex {
+    /// Builds an index over the ground edges of a level.
+    pub fn new(level: &Level) -> Self {
+        SpatialIndex::from_polygons(&level.polygons)
+    }
+
+    /// Builds an index over the edges of the ground polygons. Edge polygon indices refer to
+    /// `polygons`.
+    pub fn from_polygons(polygons: &[Polygon]) -> Self {
+        let mut edges = vec![];
+        for (p, polygon) in polygons.iter().enumerate().filter(|(_, p)| !p.grass) {
+            let vertices = &polygon.vertices;
+            for (v, from) in vertices.iter().enumerate() {
+                edges.push(Edge {
+                    polygon: p,
+                    vertex: v,
+                    from: *from,
+                    to: vertices[(v + 1) % vertices.len()],
+                });
+            }
+        }
+
+        let mut min = Position::new(f64::INFINITY, f64::INFINITY);
+        let mut max = Position::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for point in edges.iter().map(|e| e.from) {
+            min = Position::new(min.x.min(point.x), min.y.min(point.y));
+            max = Position::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        if edges.is_empty() {
+            min = Position::new(0., 0.);
+            max = min;
+        }
+
+        // Aim for a few edges per cell.
+        let side = ((edges.len() as f64).sqrt().ceil() as usize).clamp(1, MAX_CELLS);
+        let cell_size = ((max.x - min.x).max(max.y - min.y) / side as f64).max(1e-3);
+        let columns = (((max.x - min.x) / cell_size) as usize + 1).min(MAX_CELLS);
+        let rows = (((max.y - min.y) / cell_size) as usize + 1).min(MAX_CELLS);
+
+        let mut index = SpatialIndex {
+            edges,
+            origin: min,
+            cell_size,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows],
+        };
+        for (n, edge) in index.edges.iter().enumerate() {
+            let (x0, y0) = index.cell(&edge.from);
+            let (x1, y1) = index.cell(&edge.to);
+            for y in y0.min(y1)..=y0.max(y1) {
+                for x in x0.min(x1)..=x0.max(x1) {
+                    index.cells[y * columns + x].push(n);
+                }
+            }
+        }
+        index
+    }
+
+    /// All indexed edges.
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Finds the edge closest to a point.
+    ///
+    /// Returns `None` when there are no ground edges.
+    pub fn nearest_edge(&self, point: Position<f64>) -> Option<EdgeHit> {
+        let (cx, cy) = self.cell(&point);
+        let mut best: Option<EdgeHit> = None;
+        for ring in 0..self.columns.max(self.rows) {
+            let (x0, x1) = (cx.saturating_sub(ring), (cx + ring).min(self.columns - 1));
+            let (y0, y1) = (cy.saturating_sub(ring), (cy + ring).min(self.rows - 1));
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    if x != x0 && x != x1 && y != y0 && y != y1 {
+                        continue;
+                    }
+                    for &n in &self.cells[y * self.columns + x] {
+                        let edge = self.edges[n];
+                        let closest = closest_point(&edge, &point);
+                        let hit_distance = distance(&point, &closest);
+                        if best.is_none_or(|b| hit_distance < b.distance) {
+                            best = Some(EdgeHit {
+                                edge,
+                                point: closest,
+                                distance: hit_distance,
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Edges outside the searched cells are at least as far as the closest border of
+            // the searched area that has cells beyond it.
+            let (left, bottom) = self.corner(x0, y0);
+            let (right, top) = self.corner(x1 + 1, y1 + 1);
+            let mut reach = f64::INFINITY;
+            if x0 > 0 {
+                reach = reach.min(point.x - left);
+            }
+            if x1 + 1 < self.columns {
+                reach = reach.min(right - point.x);
+            }
+            if y0 > 0 {
+                reach = reach.min(point.y - bottom);
+            }
+            if y1 + 1 < self.rows {
+                reach = reach.min(top - point.y);
+            }
+            if best.is_some_and(|b| b.distance <= reach) {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Finds the edges that touch the rectangle between the corners `min` and `max`.
+    pub fn edges_in_rect(&self, min: Position<f64>, max: Position<f64>) -> Vec<Edge> {
+        if self.edges.is_empty() || min.x > max.x || min.y > max.y {
+            return vec![];
+        }
+        let (x0, y0) = self.cell(&min);
+        let (x1, y1) = self.cell(&max);
+        let mut found: Vec<usize> = vec![];
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                found.extend(&self.cells[y * self.columns + x]);
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+            .into_iter()
+            .map(|n| self.edges[n])
+            .filter(|edge| touches_rect(edge, &min, &max))
+            .collect()
+    }
+
+    /// Finds the first edge hit by a ray from `origin` going in `direction`, within
+    /// `max_distance` level units.
+    pub fn raycast(
+        &self,
+        origin: Position<f64>,
+        direction: Position<f64>,
+        max_distance: f64,
+    ) -> Option<EdgeHit> {
+        let length = direction.x.hypot(direction.y);
+        if self.edges.is_empty() || length == 0. {
+            return None;
+        }
+        let dir = Position::new(direction.x / length, direction.y / length);
+
+        // Clip the ray to the grid.
+        let (right, top) = self.corner(self.columns, self.rows);
+        let mut enter = 0_f64;
+        let mut exit = max_distance;
+        for &(o, d, low, high) in &[
+            (origin.x, dir.x, self.origin.x, right),
+            (origin.y, dir.y, self.origin.y, top),
+        ] {
+            if d == 0. {
+                if o < low || o > high {
+                    return None;
+                }
+            } else {
+                let (a, b) = ((low - o) / d, (high - o) / d);
+                enter = enter.max(a.min(b));
+                exit = exit.min(a.max(b));
+            }
+        }
+        if enter > exit {
+            return None;
+        }
+
+        // Walk the cells along the ray.
+        let start = Position::new(origin.x + dir.x * enter, origin.y + dir.y * enter);
+        let (mut x, mut y) = self.cell(&start);
+        let boundary = |cell: usize, o: f64, d: f64, low: f64| {
+            let next = if d > 0. { cell + 1 } else { cell };
+            (low + next as f64 * self.cell_size - o) / d
+        };
+        let mut next_x = if dir.x == 0. {
+            f64::INFINITY
+        } else {
+            boundary(x, origin.x, dir.x, self.origin.x)
+        };
+        let mut next_y = if dir.y == 0. {
+            f64::INFINITY
+        } else {
+            boundary(y, origin.y, dir.y, self.origin.y)
+        };
+        let step_x = self.cell_size / dir.x.abs();
+        let step_y = self.cell_size / dir.y.abs();
+
+        let mut best: Option<EdgeHit> = None;
+        loop {
+            for &n in &self.cells[y * self.columns + x] {
+                let edge = self.edges[n];
+                if let Some(t) = ray_hit(&edge, &origin, &dir) {
+                    if t <= max_distance && best.is_none_or(|b| t < b.distance) {
+                        best = Some(EdgeHit {
+                            edge,
+                            point: Position::new(origin.x + dir.x * t, origin.y + dir.y * t),
+                            distance: t,
+                        });
+                    }
+                }
+            }
+
+            let leave = next_x.min(next_y);
+            if best.is_some_and(|b| b.distance <= leave) || leave > exit {
+                break;
+            }
+            if next_x < next_y {
+                if dir.x > 0. && x + 1 < self.columns {
+                    x += 1;
+                } else if dir.x < 0. && x > 0 {
+                    x -= 1;
+                } else {
+                    break;
+                }
+                next_x += step_x;
+            } else {
+                if dir.y > 0. && y + 1 < self.rows {
+                    y += 1;
+                } else if dir.y < 0. && y > 0 {
+                    y -= 1;
+                } else {
+                    break;
+                }
+                next_y += step_y;
+            }
+        }
+        best
+    }
+
+    /// Moves an object straight down until it rests on the ground below it, treating it as a
+    /// circle with `OBJECT_RADIUS`. Returns how far it was moved, or `None` if there is no
+    /// ground below, in which case the object is left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::lev::*;
+    /// # use elma::spatial::*;
+    /// # use elma::Position;
+    /// let level = Level::new();
+    /// let index = SpatialIndex::new(&level);
+    /// let mut apple = Object::new();
+    /// apple.position = Position::new(5., 5.);
+    /// let fallen = index.drop_object(&mut apple).unwrap();
+    /// assert!((fallen - 4.6).abs() < 1e-9);
+    /// assert!((apple.position.y - 0.4).abs() < 1e-9);
+    /// ```
+    pub fn drop_object(&self, object: &mut Object) -> Option<f64> {
+        let center = object.position;
+        let r = OBJECT_RADIUS;
+        let candidates = self.edges_in_rect(
+            Position::new(center.x - r, self.origin.y),
+            Position::new(center.x + r, center.y + r),
+        );
+
+        let mut fall: Option<f64> = None;
+        let mut consider = |t: f64| {
+            // Allow for rounding when the object already rests on the ground.
+            if t >= -1e-9 && fall.is_none_or(|f| t < f) {
+                fall = Some(t.max(0.));
+            }
+        };
+        for edge in &candidates {
+            // Resting on a vertex.
+            let dx = edge.from.x - center.x;
+            if dx.abs() <= r {
+                consider(center.y - edge.from.y - (r * r - dx * dx).sqrt());
+            }
+
+            // Resting on the inside of an edge, touching it at the lowest point of the circle
+            // along the edge's upward normal.
+            let (ex, ey) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+            if ex == 0. {
+                continue;
+            }
+            let length = ex.hypot(ey);
+            let (nx, ny) = if ex > 0. {
+                (-ey / length, ex / length)
+            } else {
+                (ey / length, -ex / length)
+            };
+            let px = center.x - r * nx;
+            if px >= edge.from.x.min(edge.to.x) && px <= edge.from.x.max(edge.to.x) {
+                let surface = edge.from.y + (px - edge.from.x) * ey / ex;
+                consider(center.y - r * ny - surface);
+            }
+        }
+
+        if let Some(t) = fall {
+            object.position.y -= t;
+        }
+        fall
+    }
+
+    fn cell(&self, point: &Position<f64>) -> (usize, usize) {
+        let index = |value: f64, low: f64, count: usize| {
+            let n = ((value - low) / self.cell_size).floor();
+            if n > 0. {
+                (n as usize).min(count - 1)
+            } else {
+                0
+            }
+        };
+        (
+            index(point.x, self.origin.x, self.columns),
+            index(point.y, self.origin.y, self.rows),
+        )
+    }
+
+    fn corner(&self, x: usize, y: usize) -> (f64, f64) {
+        (
+            self.origin.x + x as f64 * self.cell_size,
+            self.origin.y + y as f64 * self.cell_size,
+        )
+    }
+}
+
+fn closest_point(edge: &Edge, point: &Position<f64>) -> Position<f64> {
+    let (ex, ey) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+    let length = ex * ex + ey * ey;
+    if length == 0. {
+        return edge.from;
+    }
+    let t = (((point.x - edge.from.x) * ex + (point.y - edge.from.y) * ey) / length).clamp(0., 1.);
+    Position::new(edge.from.x + ex * t, edge.from.y + ey * t)
+}
+
+// Distance along the ray to where it crosses the edge.
+fn ray_hit(edge: &Edge, origin: &Position<f64>, dir: &Position<f64>) -> Option<f64> {
+    let (ex, ey) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+    let denominator = cross(dir.x, dir.y, ex, ey);
+    if denominator == 0. {
+        return None;
+    }
+    let (ox, oy) = (edge.from.x - origin.x, edge.from.y - origin.y);
+    let t = cross(ox, oy, ex, ey) / denominator;
+    let s = cross(ox, oy, dir.x, dir.y) / denominator;
+    if t >= 0. && (0. ..=1.).contains(&s) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+// Clips the edge to the rectangle, Liang-Barsky style.
+fn touches_rect(edge: &Edge, min: &Position<f64>, max: &Position<f64>) -> bool {
+    let (dx, dy) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+    let (mut enter, mut exit) = (0_f64, 1_f64);
+    for &(p, q) in &[
+        (-dx, edge.from.x - min.x),
+        (dx, max.x - edge.from.x),
+        (-dy, edge.from.y - min.y),
+        (dy, max.y - edge.from.y),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return false;
+            }
+        } else if p < 0. {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+    enter <= exit
+}
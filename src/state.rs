@@ -4,6 +4,7 @@ use constants::TOP10_SIZE;
 use nom::{le_i32, le_u32, le_u8};
 use shared::TimeEntry;
 use std::fs;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::str;
 use utils::boolean;
@@ -195,7 +196,7 @@ named!(playerentry<PlayerEntry>,
 #[cfg_attr(rustfmt, rustfmt_skip)]
 named_args!(playernames(num: usize)<Vec<&str>>,
   do_parse!(
-    names: many_m_n!(num, num, apply!(null_padded_string, PLAYER_NAME_SIZE)) >>
+    names: count!(apply!(null_padded_string, PLAYER_NAME_SIZE), num) >>
     cond_reduce!(num <= TOP10_ENTRIES, count!(apply!(null_padded_string, PLAYER_NAME_SIZE), TOP10_ENTRIES - num)) >>
     (names)
   )
@@ -205,7 +206,7 @@ named_args!(playernames(num: usize)<Vec<&str>>,
 named!(top10part<Vec<TimeEntry>>,
   do_parse!(
     num_times: map!(le_u32, |x| x as usize) >>
-    times: many_m_n!(num_times, num_times, map!(le_i32, |x| x.into())) >>
+    times: count!(map!(le_i32, |x| x.into()), num_times) >>
     cond_reduce!(num_times <= TOP10_ENTRIES, count!(le_i32, TOP10_ENTRIES - num_times)) >>
     player_a_names: apply!(playernames, num_times) >>
     player_b_names: apply!(playernames, num_times) >>
@@ -359,11 +360,26 @@ impl State {
         State::parse(buffer.as_ref())
     }
 
+    /// Reads a state.dat file from a reader.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::state::*;
+    /// let file = std::fs::File::open("tests/assets/state/state.dat").unwrap();
+    /// let state = State::read_from(file).unwrap();
+    /// ```
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self, ElmaError> {
+        let mut buffer = vec![];
+        reader.read_to_end(&mut buffer)?;
+        State::parse(&buffer)
+    }
+
     fn parse(buffer: &[u8]) -> Result<Self, ElmaError> {
         let mut v = buffer.to_vec();
         {
             let mut buf = &mut v[..];
-            crypt_whole_state(&mut buf);
+            crypt_whole_state(&mut buf)?;
         }
         match parse_state(&v) {
             Ok((_, state)) => Ok(state),
@@ -437,10 +453,27 @@ impl State {
             LEVEL_NAME_SIZE,
         )?);
         buffer.write_u32::<LE>(STATE_END)?;
-        crypt_whole_state(&mut buffer[..]);
+        crypt_whole_state(&mut buffer[..])?;
         Ok(buffer)
     }
 
+    /// Writes state.dat in its binary form to a writer. The state is encrypted as a whole, so
+    /// it is built in memory before being written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use elma::state::*;
+    /// let state = State::new();
+    /// let mut buffer = vec![];
+    /// state.write_to(&mut buffer).unwrap();
+    /// assert_eq!(buffer, state.to_bytes().unwrap());
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), ElmaError> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
     /// Save state.dat
     ///
     /// # Examples
@@ -459,7 +492,7 @@ impl State {
     }
 }
 
-fn crypt_whole_state(buf: &mut [u8]) {
+fn crypt_whole_state(buf: &mut [u8]) -> Result<(), ElmaError> {
     let state_pieces = [
         4,
         61920,
@@ -485,9 +518,16 @@ fn crypt_whole_state(buf: &mut [u8]) {
     ];
     let mut curr = 0;
     for p in &state_pieces {
+        if curr + p > buf.len() {
+            return Err(ElmaError::UnexpectedEof {
+                offset: curr,
+                section: "state",
+            });
+        }
         crypt_state(&mut buf[curr..curr + p]);
         curr += p;
     }
+    Ok(())
 }
 
 fn crypt_state(buffer: &mut [u8]) {
@@ -0,0 +1,927 @@
+use super::{
+    geometry::simplify_ring,
+    lev::{GravityDirection, Level, Object, ObjectType, Polygon},
+    ElmaError, Position,
+};
+use std::f64::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+
+// Affine transform `[a, b, c, d, e, f]`, mapping (x, y) to (ax + cy + e, bx + dy + f).
+type Transform = [f64; 6];
+
+const IDENTITY: Transform = [1., 0., 0., 1., 0., 0.];
+// Subdivision limit for flattening a single Bézier curve.
+const MAX_DEPTH: u32 = 16;
+// Elements whose contents are never drawn directly.
+const HIDDEN_ELEMENTS: &[&str] = &[
+    "clipPath",
+    "defs",
+    "desc",
+    "filter",
+    "linearGradient",
+    "marker",
+    "mask",
+    "metadata",
+    "pattern",
+    "radialGradient",
+    "style",
+    "symbol",
+    "title",
+];
+
+/// SVG import errors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgError {
+    /// Malformed XML, with the byte offset of the problem.
+    InvalidXml(usize),
+    /// Malformed path data, with the offending `d` attribute.
+    InvalidPathData(String),
+    /// Malformed `transform` attribute.
+    InvalidTransform(String),
+    /// Attribute that should be a number but is not.
+    InvalidNumber(String),
+}
+
+/// Options for `import_svg`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// Level units per SVG user unit.
+    pub scale: f64,
+    /// Largest allowed distance between a curve and the polygon edges replacing it, in level
+    /// units.
+    pub tolerance: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            scale: 0.1,
+            tolerance: 0.05,
+        }
+    }
+}
+
+impl SvgOptions {
+    /// Creates new SVG options with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Polygons and objects imported from an SVG drawing.
+#[derive(Debug, Default, PartialEq)]
+pub struct SvgImport {
+    /// Polygons from paths and shapes.
+    pub polygons: Vec<Polygon>,
+    /// Objects from tagged circles and ellipses.
+    pub objects: Vec<Object>,
+}
+
+impl SvgImport {
+    /// Creates a new level with the imported polygons and objects.
+    pub fn into_level(self) -> Level {
+        let mut level = Level::new();
+        level.polygons = self.polygons;
+        level.objects = self.objects;
+        level
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Grass,
+    Player,
+    Exit,
+    Apple,
+    Killer,
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End {
+        name: &'a str,
+        offset: usize,
+    },
+}
+
+struct Context<'a> {
+    name: &'a str,
+    transform: Transform,
+    tag: Option<Tag>,
+    hidden: bool,
+}
+
+/// Loads an SVG file and imports it with `import_svg`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use elma::svg::*;
+/// let level = load_svg("drawing.svg", &SvgOptions::new()).unwrap().into_level();
+/// ```
+pub fn load_svg<P: Into<PathBuf>>(path: P, options: &SvgOptions) -> Result<SvgImport, ElmaError> {
+    let source = fs::read_to_string(path.into())?;
+    import_svg(&source, options)
+}
+
+/// Imports the shapes of an SVG drawing as level polygons and objects.
+///
+/// Paths, rectangles, polygons, polylines, circles and ellipses become polygons, with curves
+/// and arcs flattened to within `tolerance`. Every subpath becomes its own polygon and open
+/// subpaths are closed. Coordinates are multiplied by `scale` and flipped vertically, since SVG
+/// y grows downwards.
+///
+/// Shapes are tagged by an `inkscape:label`, `id` or `class` starting with `grass`, `start`
+/// (or `player`), `exit` (or `flower`), `apple` or `killer`, and inherit the tag of their group
+/// or layer. Tagged circles and ellipses become objects at their centres, and shapes tagged
+/// `grass` become grass polygons.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::svg::*;
+/// let svg = r#"<svg xmlns="http://www.w3.org/2000/svg">
+///   <path d="M 0 0 H 100 V 70 H 0 Z"/>
+///   <circle id="start" cx="20" cy="60" r="4"/>
+///   <circle id="exit" cx="80" cy="60" r="4"/>
+/// </svg>"#;
+/// let level = import_svg(svg, &SvgOptions::new()).unwrap().into_level();
+/// assert!(level.check_topology().is_ok());
+/// ```
+pub fn import_svg(source: &str, options: &SvgOptions) -> Result<SvgImport, ElmaError> {
+    parse_svg(source, options).map_err(ElmaError::InvalidSvg)
+}
+
+fn parse_svg(source: &str, options: &SvgOptions) -> Result<SvgImport, SvgError> {
+    let mut import = SvgImport::default();
+    let root = Context {
+        name: "",
+        transform: [options.scale, 0., 0., -options.scale, 0., 0.],
+        tag: None,
+        hidden: false,
+    };
+    let mut stack = vec![root];
+
+    for token in tokenize(source)? {
+        match token {
+            Token::Start {
+                name,
+                attributes,
+                empty,
+            } => {
+                let context = {
+                    let parent = &stack[stack.len() - 1];
+                    let attribute = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|&&(k, _)| k == key)
+                            .map(|(_, v)| v.as_str())
+                    };
+                    let hidden = parent.hidden
+                        || HIDDEN_ELEMENTS.contains(&name)
+                        || attribute("display") == Some("none")
+                        || attribute("style")
+                            .is_some_and(|style| style.replace(' ', "").contains("display:none"));
+                    let transform = match attribute("transform") {
+                        Some(transform) => compose(&parent.transform, &parse_transform(transform)?),
+                        None => parent.transform,
+                    };
+                    let tag = element_tag(&attribute).or(parent.tag);
+                    if !hidden {
+                        import_shape(&mut import, name, &attribute, &transform, tag, options)?;
+                    }
+                    Context {
+                        name,
+                        transform,
+                        tag,
+                        hidden,
+                    }
+                };
+                if !empty {
+                    stack.push(context);
+                }
+            }
+            Token::End { name, offset } => {
+                if stack.len() < 2 || stack[stack.len() - 1].name != name {
+                    return Err(SvgError::InvalidXml(offset));
+                }
+                stack.pop();
+            }
+        }
+    }
+
+    if stack.len() > 1 {
+        return Err(SvgError::InvalidXml(source.len()));
+    }
+    Ok(import)
+}
+
+fn import_shape<'a, F>(
+    import: &mut SvgImport,
+    name: &str,
+    attribute: &F,
+    transform: &Transform,
+    tag: Option<Tag>,
+    options: &SvgOptions,
+) -> Result<(), SvgError>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    let number = |key: &str| attribute(key).map_or(Ok(0.), parse_length);
+    let data = match name {
+        "path" => attribute("d").unwrap_or("").to_string(),
+        "rect" => {
+            let (x, y) = (number("x")?, number("y")?);
+            let (width, height) = (number("width")?, number("height")?);
+            let (mut rx, mut ry) = (number("rx")?, number("ry")?);
+            // A missing corner radius defaults to the other one.
+            if attribute("rx").is_none() {
+                rx = ry;
+            }
+            if attribute("ry").is_none() {
+                ry = rx;
+            }
+            let rx = rx.min(width / 2.);
+            let ry = ry.min(height / 2.);
+            if rx > 0. && ry > 0. {
+                format!(
+                    "M{} {}H{}A{} {} 0 0 1 {} {}V{}A{} {} 0 0 1 {} {}H{}A{} {} 0 0 1 {} {}V{}A{} {} 0 0 1 {} {}Z",
+                    x + rx, y, x + width - rx,
+                    rx, ry, x + width, y + ry, y + height - ry,
+                    rx, ry, x + width - rx, y + height, x + rx,
+                    rx, ry, x, y + height - ry, y + ry,
+                    rx, ry, x + rx, y
+                )
+            } else {
+                format!("M{} {}h{}v{}h{}Z", x, y, width, height, -width)
+            }
+        }
+        "polygon" | "polyline" => format!("M{}Z", attribute("points").unwrap_or("")),
+        "circle" | "ellipse" => {
+            let (cx, cy) = (number("cx")?, number("cy")?);
+            if let Some(object_type) = object_type(tag) {
+                import.objects.push(Object {
+                    position: apply(transform, cx, cy),
+                    object_type,
+                });
+                return Ok(());
+            }
+            let (rx, ry) = if name == "circle" {
+                (number("r")?, number("r")?)
+            } else {
+                (number("rx")?, number("ry")?)
+            };
+            format!(
+                "M{} {}A{} {} 0 1 0 {} {}A{} {} 0 1 0 {} {}Z",
+                cx + rx,
+                cy,
+                rx,
+                ry,
+                cx - rx,
+                cy,
+                rx,
+                ry,
+                cx + rx,
+                cy
+            )
+        }
+        _ => return Ok(()),
+    };
+
+    let mut builder = PathBuilder {
+        transform: *transform,
+        tolerance: options.tolerance,
+        rings: vec![],
+        ring: vec![],
+    };
+    if parse_path(&data, &mut builder).is_none() {
+        return Err(SvgError::InvalidPathData(data));
+    }
+    builder.finish_ring();
+    for ring in builder.rings {
+        let vertices = simplify_ring(ring);
+        if vertices.len() >= 3 {
+            import.polygons.push(Polygon {
+                grass: tag == Some(Tag::Grass),
+                vertices,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn element_tag<'a, F>(attribute: &F) -> Option<Tag>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    let classes = attribute("class").unwrap_or("").split_whitespace();
+    attribute("inkscape:label")
+        .into_iter()
+        .chain(attribute("id"))
+        .chain(classes)
+        .filter_map(|value| {
+            let value = value.to_ascii_lowercase();
+            if value.starts_with("grass") {
+                Some(Tag::Grass)
+            } else if value.starts_with("start") || value.starts_with("player") {
+                Some(Tag::Player)
+            } else if value.starts_with("exit") || value.starts_with("flower") {
+                Some(Tag::Exit)
+            } else if value.starts_with("apple") {
+                Some(Tag::Apple)
+            } else if value.starts_with("killer") {
+                Some(Tag::Killer)
+            } else {
+                None
+            }
+        })
+        .next()
+}
+
+fn object_type(tag: Option<Tag>) -> Option<ObjectType> {
+    match tag {
+        Some(Tag::Player) => Some(ObjectType::Player),
+        Some(Tag::Exit) => Some(ObjectType::Exit),
+        Some(Tag::Apple) => Some(ObjectType::Apple {
+            gravity: GravityDirection::None,
+            animation: 1,
+        }),
+        Some(Tag::Killer) => Some(ObjectType::Killer),
+        _ => None,
+    }
+}
+
+fn parse_length(value: &str) -> Result<f64, SvgError> {
+    let trimmed = value.trim();
+    trimmed
+        .strip_suffix("px")
+        .unwrap_or(trimmed)
+        .parse()
+        .map_err(|_| SvgError::InvalidNumber(value.to_string()))
+}
+
+// Splits the source into start and end tags, skipping text, comments, processing
+// instructions, CDATA sections and doctype declarations.
+fn tokenize<'a>(source: &'a str) -> Result<Vec<Token<'a>>, SvgError> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut pos = 0;
+
+    while let Some(start) = source[pos..].find('<').map(|n| pos + n) {
+        let rest = &source[start..];
+        if rest.starts_with("<!--") {
+            pos = skip_past(source, start, "-->")?;
+        } else if rest.starts_with("<![CDATA[") {
+            pos = skip_past(source, start, "]]>")?;
+        } else if rest.starts_with("<?") {
+            pos = skip_past(source, start, "?>")?;
+        } else if rest.starts_with("<!") {
+            // Doctype, possibly with an internal subset in brackets.
+            let mut depth = 0;
+            pos = start;
+            loop {
+                match bytes.get(pos) {
+                    Some(b'[') => depth += 1,
+                    Some(b']') => depth -= 1,
+                    Some(b'>') if depth <= 0 => break,
+                    Some(_) => {}
+                    None => return Err(SvgError::InvalidXml(start)),
+                }
+                pos += 1;
+            }
+            pos += 1;
+        } else if rest.starts_with("</") {
+            let end = skip_past(source, start, ">")?;
+            tokens.push(Token::End {
+                name: source[start + 2..end - 1].trim(),
+                offset: start,
+            });
+            pos = end;
+        } else {
+            pos = start + 1;
+            let name_end = scan(bytes, pos, |b| !is_space(b) && b != b'/' && b != b'>');
+            let name = &source[pos..name_end];
+            if name.is_empty() {
+                return Err(SvgError::InvalidXml(start));
+            }
+            pos = name_end;
+
+            let mut attributes = vec![];
+            let empty = loop {
+                pos = scan(bytes, pos, is_space);
+                match bytes.get(pos) {
+                    Some(b'>') => break false,
+                    Some(b'/') if bytes.get(pos + 1) == Some(&b'>') => {
+                        pos += 1;
+                        break true;
+                    }
+                    Some(_) => {}
+                    None => return Err(SvgError::InvalidXml(start)),
+                }
+                let key_end = scan(bytes, pos, |b| !is_space(b) && b != b'=' && b != b'>');
+                let key = &source[pos..key_end];
+                pos = scan(bytes, key_end, is_space);
+                if key.is_empty() || bytes.get(pos) != Some(&b'=') {
+                    return Err(SvgError::InvalidXml(pos));
+                }
+                pos = scan(bytes, pos + 1, is_space);
+                let quote = match bytes.get(pos) {
+                    Some(&q) if q == b'"' || q == b'\'' => q,
+                    _ => return Err(SvgError::InvalidXml(pos)),
+                };
+                let value_end = scan(bytes, pos + 1, |b| b != quote);
+                if value_end >= bytes.len() {
+                    return Err(SvgError::InvalidXml(pos));
+                }
+                attributes.push((key, decode_entities(&source[pos + 1..value_end])));
+                pos = value_end + 1;
+            };
+            pos += 1;
+            tokens.push(Token::Start {
+                name,
+                attributes,
+                empty,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn skip_past(source: &str, start: usize, end: &str) -> Result<usize, SvgError> {
+    source[start..]
+        .find(end)
+        .map(|n| start + n + end.len())
+        .ok_or(SvgError::InvalidXml(start))
+}
+
+fn scan<F: Fn(u8) -> bool>(bytes: &[u8], mut pos: usize, accept: F) -> usize {
+    while pos < bytes.len() && accept(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\n' || b == b'\r'
+}
+
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(::std::char::from_u32),
+            _ if entity.starts_with('#') => {
+                entity[1..].parse().ok().and_then(::std::char::from_u32)
+            }
+            _ => None,
+        };
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+// Reads numbers, flags and command letters from path data and other number lists.
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(data: &'a str) -> Self {
+        Scanner {
+            bytes: data.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn skip_separators(&mut self) {
+        self.pos = scan(self.bytes, self.pos, |b| is_space(b) || b == b',');
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.bytes.len()
+    }
+
+    // Next byte if it is a letter, without consuming it.
+    fn peek_letter(&mut self) -> Option<u8> {
+        self.skip_separators();
+        self.bytes
+            .get(self.pos)
+            .cloned()
+            .filter(|b| b.is_ascii_alphabetic())
+    }
+
+    fn starts_number(&mut self) -> bool {
+        self.skip_separators();
+        match self.bytes.get(self.pos) {
+            Some(&b) => b.is_ascii_digit() || b == b'-' || b == b'+' || b == b'.',
+            None => false,
+        }
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |pos| scan(self.bytes, pos, |b| b.is_ascii_digit());
+        let mut pos = start;
+        if let Some(b'-') | Some(b'+') = self.bytes.get(pos) {
+            pos += 1;
+        }
+        let int_end = digits(pos);
+        let mut end = int_end;
+        if self.bytes.get(end) == Some(&b'.') {
+            end = digits(end + 1);
+        }
+        if end == pos || (end == pos + 1 && int_end == pos) {
+            return None;
+        }
+        if let Some(b'e') | Some(b'E') = self.bytes.get(end) {
+            let mut exponent = end + 1;
+            if let Some(b'-') | Some(b'+') = self.bytes.get(exponent) {
+                exponent += 1;
+            }
+            let exponent_end = digits(exponent);
+            if exponent_end > exponent {
+                end = exponent_end;
+            }
+        }
+        self.pos = end;
+        ::std::str::from_utf8(&self.bytes[start..end])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    // Arc flags are single digits and need no separator after them.
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.bytes.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn pair(&mut self) -> Option<(f64, f64)> {
+        Some((self.number()?, self.number()?))
+    }
+}
+
+fn parse_transform(value: &str) -> Result<Transform, SvgError> {
+    let invalid = || SvgError::InvalidTransform(value.to_string());
+    let mut transform = IDENTITY;
+    let mut rest = value.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(invalid)?;
+        let close = rest.find(')').ok_or_else(invalid)?;
+        if close < open {
+            return Err(invalid());
+        }
+        let mut scanner = Scanner::new(&rest[open + 1..close]);
+        let mut args = vec![];
+        while !scanner.at_end() {
+            args.push(scanner.number().ok_or_else(invalid)?);
+        }
+
+        let next = match (rest[..open].trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => [a, b, c, d, e, f],
+            ("translate", &[x]) => [1., 0., 0., 1., x, 0.],
+            ("translate", &[x, y]) => [1., 0., 0., 1., x, y],
+            ("scale", &[s]) => [s, 0., 0., s, 0., 0.],
+            ("scale", &[x, y]) => [x, 0., 0., y, 0., 0.],
+            ("rotate", &[angle]) => rotation(angle),
+            ("rotate", &[angle, x, y]) => compose(
+                &compose(&[1., 0., 0., 1., x, y], &rotation(angle)),
+                &[1., 0., 0., 1., -x, -y],
+            ),
+            ("skewX", &[angle]) => [1., 0., angle.to_radians().tan(), 1., 0., 0.],
+            ("skewY", &[angle]) => [1., angle.to_radians().tan(), 0., 1., 0., 0.],
+            _ => return Err(invalid()),
+        };
+        transform = compose(&transform, &next);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(transform)
+}
+
+fn rotation(degrees: f64) -> Transform {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    [cos, sin, -sin, cos, 0., 0.]
+}
+
+// Transform applying `inner` first, then `outer`.
+fn compose(outer: &Transform, inner: &Transform) -> Transform {
+    let [a, b, c, d, e, f] = *outer;
+    [
+        a * inner[0] + c * inner[1],
+        b * inner[0] + d * inner[1],
+        a * inner[2] + c * inner[3],
+        b * inner[2] + d * inner[3],
+        a * inner[4] + c * inner[5] + e,
+        b * inner[4] + d * inner[5] + f,
+    ]
+}
+
+fn apply(transform: &Transform, x: f64, y: f64) -> Position<f64> {
+    let [a, b, c, d, e, f] = *transform;
+    Position::new(a * x + c * y + e, b * x + d * y + f)
+}
+
+// Largest factor by which the transform stretches a distance.
+fn max_scale(transform: &Transform) -> f64 {
+    let [a, b, c, d, _, _] = *transform;
+    let sum = a * a + b * b + c * c + d * d;
+    let det = a * d - b * c;
+    ((sum + (sum * sum - 4. * det * det).max(0.).sqrt()) / 2.).sqrt()
+}
+
+// Collects flattened subpaths in level coordinates.
+struct PathBuilder {
+    transform: Transform,
+    tolerance: f64,
+    rings: Vec<Vec<Position<f64>>>,
+    ring: Vec<Position<f64>>,
+}
+
+impl PathBuilder {
+    fn finish_ring(&mut self) {
+        if !self.ring.is_empty() {
+            let ring = ::std::mem::take(&mut self.ring);
+            self.rings.push(ring);
+        }
+    }
+
+    fn move_to(&mut self, x: f64, y: f64) {
+        self.finish_ring();
+        self.ring.push(apply(&self.transform, x, y));
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.ring.push(apply(&self.transform, x, y));
+    }
+
+    fn cubic_to(&mut self, from: (f64, f64), c1: (f64, f64), c2: (f64, f64), to: (f64, f64)) {
+        let points = [
+            apply(&self.transform, from.0, from.1),
+            apply(&self.transform, c1.0, c1.1),
+            apply(&self.transform, c2.0, c2.1),
+            apply(&self.transform, to.0, to.1),
+        ];
+        flatten_cubic(&points, self.tolerance, 0, &mut self.ring);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn arc_to(
+        &mut self,
+        from: (f64, f64),
+        radii: (f64, f64),
+        angle: f64,
+        large_arc: bool,
+        sweep: bool,
+        to: (f64, f64),
+    ) {
+        let (rx, ry) = (radii.0.abs(), radii.1.abs());
+        if rx == 0. || ry == 0. {
+            return self.line_to(to.0, to.1);
+        }
+        if from == to {
+            return;
+        }
+
+        // Endpoint to centre parameterization, as in the SVG implementation notes.
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let (hx, hy) = ((from.0 - to.0) / 2., (from.1 - to.1) / 2.);
+        let x1 = cos * hx + sin * hy;
+        let y1 = -sin * hx + cos * hy;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        let (rx, ry) = if lambda > 1. {
+            (rx * lambda.sqrt(), ry * lambda.sqrt())
+        } else {
+            (rx, ry)
+        };
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let cx1 = coefficient * rx * y1 / ry;
+        let cy1 = -coefficient * ry * x1 / rx;
+        let cx = cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.;
+        let cy = sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.;
+
+        let vector_angle =
+            |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+        let start = vector_angle(1., 0., (x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut sweep_angle = vector_angle(
+            (x1 - cx1) / rx,
+            (y1 - cy1) / ry,
+            (-x1 - cx1) / rx,
+            (-y1 - cy1) / ry,
+        );
+        if !sweep && sweep_angle > 0. {
+            sweep_angle -= 2. * PI;
+        } else if sweep && sweep_angle < 0. {
+            sweep_angle += 2. * PI;
+        }
+
+        let segments = arc_segments(
+            rx.max(ry),
+            sweep_angle,
+            self.tolerance / max_scale(&self.transform),
+        );
+        for n in 1..segments {
+            let t = start + sweep_angle * n as f64 / segments as f64;
+            let (x, y) = (rx * t.cos(), ry * t.sin());
+            self.line_to(cx + cos * x - sin * y, cy + sin * x + cos * y);
+        }
+        self.line_to(to.0, to.1);
+    }
+}
+
+// Number of chords keeping an arc of the given radius within `tolerance`.
+fn arc_segments(radius: f64, sweep: f64, tolerance: f64) -> usize {
+    let step = if tolerance < radius {
+        2. * (1. - tolerance / radius).acos()
+    } else {
+        PI / 2.
+    };
+    ((sweep.abs() / step).ceil() as usize).clamp(1, 1024)
+}
+
+fn flatten_cubic(
+    points: &[Position<f64>; 4],
+    tolerance: f64,
+    depth: u32,
+    out: &mut Vec<Position<f64>>,
+) {
+    let [p0, p1, p2, p3] = *points;
+    if depth >= MAX_DEPTH
+        || (line_distance(&p1, &p0, &p3) <= tolerance && line_distance(&p2, &p0, &p3) <= tolerance)
+    {
+        out.push(p3);
+        return;
+    }
+    let mid =
+        |a: Position<f64>, b: Position<f64>| Position::new((a.x + b.x) / 2., (a.y + b.y) / 2.);
+    let (p01, p12, p23) = (mid(p0, p1), mid(p1, p2), mid(p2, p3));
+    let (p012, p123) = (mid(p01, p12), mid(p12, p23));
+    let split = mid(p012, p123);
+    flatten_cubic(&[p0, p01, p012, split], tolerance, depth + 1, out);
+    flatten_cubic(&[split, p123, p23, p3], tolerance, depth + 1, out);
+}
+
+// Distance from `point` to the line through `a` and `b`.
+fn line_distance(point: &Position<f64>, a: &Position<f64>, b: &Position<f64>) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx.hypot(dy);
+    if length == 0. {
+        return (point.x - a.x).hypot(point.y - a.y);
+    }
+    ((point.x - a.x) * dy - (point.y - a.y) * dx).abs() / length
+}
+
+// Parses path data into the builder, returning `None` on malformed data.
+fn parse_path(data: &str, builder: &mut PathBuilder) -> Option<()> {
+    let mut scanner = Scanner::new(data);
+    let mut current = (0., 0.);
+    let mut start = (0., 0.);
+    // Second control point of the previous cubic, or control point of the previous quadratic.
+    let mut last_cubic: Option<(f64, f64)> = None;
+    let mut last_quadratic: Option<(f64, f64)> = None;
+    let mut command = None;
+
+    while !scanner.at_end() {
+        if let Some(letter) = scanner.peek_letter() {
+            scanner.pos += 1;
+            command = Some(letter);
+        } else if command.is_none() || !scanner.starts_number() {
+            return None;
+        }
+        let letter = command?;
+        let relative = letter.is_ascii_lowercase();
+        let offset = if relative { current } else { (0., 0.) };
+        let point =
+            |scanner: &mut Scanner| scanner.pair().map(|(x, y)| (x + offset.0, y + offset.1));
+        let mut cubic = None;
+        let mut quadratic = None;
+
+        match letter.to_ascii_uppercase() {
+            b'M' => {
+                current = point(&mut scanner)?;
+                start = current;
+                builder.move_to(current.0, current.1);
+                // Further coordinate pairs are implicit line commands.
+                command = Some(if relative { b'l' } else { b'L' });
+            }
+            b'L' => {
+                current = point(&mut scanner)?;
+                builder.line_to(current.0, current.1);
+            }
+            b'H' => {
+                current.0 = scanner.number()? + offset.0;
+                builder.line_to(current.0, current.1);
+            }
+            b'V' => {
+                current.1 = scanner.number()? + offset.1;
+                builder.line_to(current.0, current.1);
+            }
+            b'C' | b'S' => {
+                let c1 = if letter.eq_ignore_ascii_case(&b'C') {
+                    point(&mut scanner)?
+                } else {
+                    reflect(last_cubic, current)
+                };
+                let c2 = point(&mut scanner)?;
+                let to = point(&mut scanner)?;
+                builder.cubic_to(current, c1, c2, to);
+                cubic = Some(c2);
+                current = to;
+            }
+            b'Q' | b'T' => {
+                let control = if letter.eq_ignore_ascii_case(&b'Q') {
+                    point(&mut scanner)?
+                } else {
+                    reflect(last_quadratic, current)
+                };
+                let to = point(&mut scanner)?;
+                // Degree elevation to a cubic.
+                let c1 = (
+                    current.0 + 2. / 3. * (control.0 - current.0),
+                    current.1 + 2. / 3. * (control.1 - current.1),
+                );
+                let c2 = (
+                    to.0 + 2. / 3. * (control.0 - to.0),
+                    to.1 + 2. / 3. * (control.1 - to.1),
+                );
+                builder.cubic_to(current, c1, c2, to);
+                quadratic = Some(control);
+                current = to;
+            }
+            b'A' => {
+                let radii = scanner.pair()?;
+                let angle = scanner.number()?;
+                let large_arc = scanner.flag()?;
+                let sweep = scanner.flag()?;
+                let to = point(&mut scanner)?;
+                builder.arc_to(current, radii, angle, large_arc, sweep, to);
+                current = to;
+            }
+            b'Z' => {
+                current = start;
+                builder.finish_ring();
+                // A command after closing starts a new subpath at the same point.
+                builder
+                    .ring
+                    .push(apply(&builder.transform, start.0, start.1));
+                command = None;
+            }
+            _ => return None,
+        }
+        last_cubic = cubic;
+        last_quadratic = quadratic;
+    }
+    Some(())
+}
+
+fn reflect(control: Option<(f64, f64)>, current: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2. * current.0 - x, 2. * current.1 - y),
+        None => current,
+    }
+}
@@ -0,0 +1,205 @@
+use super::{geometry::union, lev::Polygon, Position};
+use std::f64::consts::PI;
+
+// Glyphs are drawn on a grid this many units wide and high.
+const GLYPH_WIDTH: f64 = 4.;
+const GLYPH_HEIGHT: f64 = 6.;
+// Distance between baselines, relative to the text size.
+const LINE_HEIGHT: f64 = 1.5;
+
+type Glyph = &'static [&'static [(i8, i8)]];
+
+/// Text style used by `text_polygons`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    /// Height of capital letters, in level units.
+    pub size: f64,
+    /// Width of the letter strokes, in level units.
+    pub thickness: f64,
+    /// Extra space between letters, in level units.
+    pub spacing: f64,
+    /// Counter-clockwise rotation around the text position, in radians.
+    pub rotation: f64,
+    /// Produce grass polygons instead of ground polygons.
+    pub grass: bool,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            size: 2.,
+            thickness: 0.3,
+            spacing: 0.6,
+            rotation: 0.,
+            grass: false,
+        }
+    }
+}
+
+impl TextStyle {
+    /// Creates a new text style with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Turns a string into polygons using the built-in stroke font.
+///
+/// `position` is the left end of the baseline of the first line. Letters are case-insensitive,
+/// `\n` starts a new line, and characters missing from the font are drawn as `?`. Each letter
+/// becomes one or more non-intersecting polygons; letters with enclosed areas such as `O` give
+/// an outline and a hole, following the even-odd rule.
+///
+/// # Examples
+///
+/// ```rust
+/// # use elma::text::*;
+/// # use elma::Position;
+/// let mut level = elma::lev::Level::new();
+/// let style = TextStyle { grass: true, ..TextStyle::new() };
+/// level.polygons.extend(text_polygons("ELMA", Position::new(1., 2.), &style));
+/// ```
+pub fn text_polygons(text: &str, position: Position<f64>, style: &TextStyle) -> Vec<Polygon> {
+    let scale = style.size / GLYPH_HEIGHT;
+    let width = style.thickness / scale;
+    let (sin, cos) = style.rotation.sin_cos();
+    let mut polygons = vec![];
+    let mut pen = Position::new(0., 0.);
+
+    for c in text.chars() {
+        if c == '\n' {
+            pen.x = 0.;
+            pen.y -= style.size * LINE_HEIGHT;
+            continue;
+        }
+
+        for mut polygon in glyph_outline(glyph(c), width) {
+            for vertex in &mut polygon.vertices {
+                let x = pen.x + vertex.x * scale;
+                let y = pen.y + vertex.y * scale;
+                vertex.x = position.x + x * cos - y * sin;
+                vertex.y = position.y + x * sin + y * cos;
+            }
+            polygon.grass = style.grass;
+            polygons.push(polygon);
+        }
+        pen.x += GLYPH_WIDTH * scale + style.spacing;
+    }
+
+    polygons
+}
+
+// Outline of a glyph: every stroke point becomes an octagon, every stroke segment a rectangle,
+// and the union of all of them is the letter.
+fn glyph_outline(strokes: Glyph, width: f64) -> Vec<Polygon> {
+    let mut outline = vec![];
+    let half = width / 2.;
+    // Circumradius giving the octagon flat sides at exactly half the stroke width.
+    let radius = half / (PI / 8.).cos();
+
+    for stroke in strokes {
+        for &(x, y) in stroke.iter() {
+            let vertices = (0..8)
+                .map(|n| {
+                    let angle = PI / 8. + f64::from(n) * PI / 4.;
+                    Position::new(
+                        f64::from(x) + radius * angle.cos(),
+                        f64::from(y) + radius * angle.sin(),
+                    )
+                })
+                .collect();
+            outline = union(
+                &outline,
+                &[Polygon {
+                    grass: false,
+                    vertices,
+                }],
+            );
+        }
+
+        for pair in stroke.windows(2) {
+            let (ax, ay) = (f64::from(pair[0].0), f64::from(pair[0].1));
+            let (bx, by) = (f64::from(pair[1].0), f64::from(pair[1].1));
+            let length = (bx - ax).hypot(by - ay);
+            if length == 0. {
+                continue;
+            }
+            let nx = -(by - ay) / length * half;
+            let ny = (bx - ax) / length * half;
+            let rectangle = Polygon {
+                grass: false,
+                vertices: vec![
+                    Position::new(ax + nx, ay + ny),
+                    Position::new(ax - nx, ay - ny),
+                    Position::new(bx - nx, by - ny),
+                    Position::new(bx + nx, by + ny),
+                ],
+            };
+            outline = union(&outline, &[rectangle]);
+        }
+    }
+
+    outline
+}
+
+#[rustfmt::skip]
+fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        ' ' => &[],
+        'A' => &[&[(0, 0), (0, 4), (2, 6), (4, 4), (4, 0)], &[(0, 3), (4, 3)]],
+        'B' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)],
+                 &[(3, 3), (4, 2), (4, 1), (3, 0), (0, 0)]],
+        'C' => &[&[(4, 6), (1, 6), (0, 5), (0, 1), (1, 0), (4, 0)]],
+        'D' => &[&[(0, 0), (0, 6), (2, 6), (4, 4), (4, 2), (2, 0), (0, 0)]],
+        'E' => &[&[(4, 6), (0, 6), (0, 0), (4, 0)], &[(0, 3), (3, 3)]],
+        'F' => &[&[(4, 6), (0, 6), (0, 0)], &[(0, 3), (3, 3)]],
+        'G' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 1), (1, 0), (3, 0), (4, 1), (4, 3), (2, 3)]],
+        'H' => &[&[(0, 0), (0, 6)], &[(4, 0), (4, 6)], &[(0, 3), (4, 3)]],
+        'I' => &[&[(1, 6), (3, 6)], &[(2, 6), (2, 0)], &[(1, 0), (3, 0)]],
+        'J' => &[&[(4, 6), (4, 1), (3, 0), (1, 0), (0, 1)]],
+        'K' => &[&[(0, 0), (0, 6)], &[(4, 6), (0, 2)], &[(1, 3), (4, 0)]],
+        'L' => &[&[(0, 6), (0, 0), (4, 0)]],
+        'M' => &[&[(0, 0), (0, 6), (2, 3), (4, 6), (4, 0)]],
+        'N' => &[&[(0, 0), (0, 6), (4, 0), (4, 6)]],
+        'O' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)]],
+        'P' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)]],
+        'Q' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)],
+                 &[(2, 2), (4, 0)]],
+        'R' => &[&[(0, 0), (0, 6), (3, 6), (4, 5), (4, 4), (3, 3), (0, 3)], &[(2, 3), (4, 0)]],
+        'S' => &[&[(4, 5), (3, 6), (1, 6), (0, 5), (0, 4), (1, 3), (3, 3), (4, 2), (4, 1), (3, 0),
+                   (1, 0), (0, 1)]],
+        'T' => &[&[(0, 6), (4, 6)], &[(2, 6), (2, 0)]],
+        'U' => &[&[(0, 6), (0, 1), (1, 0), (3, 0), (4, 1), (4, 6)]],
+        'V' => &[&[(0, 6), (2, 0), (4, 6)]],
+        'W' => &[&[(0, 6), (1, 0), (2, 3), (3, 0), (4, 6)]],
+        'X' => &[&[(0, 6), (4, 0)], &[(0, 0), (4, 6)]],
+        'Y' => &[&[(0, 6), (2, 3), (4, 6)], &[(2, 3), (2, 0)]],
+        'Z' => &[&[(0, 6), (4, 6), (0, 0), (4, 0)]],
+        '0' => &[&[(1, 0), (0, 1), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (1, 0)],
+                 &[(1, 1), (3, 5)]],
+        '1' => &[&[(1, 5), (2, 6), (2, 0)], &[(1, 0), (3, 0)]],
+        '2' => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (0, 0), (4, 0)]],
+        '3' => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (3, 3), (4, 2), (4, 1), (3, 0), (1, 0),
+                   (0, 1)], &[(1, 3), (3, 3)]],
+        '4' => &[&[(3, 0), (3, 6), (0, 2), (4, 2)]],
+        '5' => &[&[(4, 6), (0, 6), (0, 3), (3, 3), (4, 2), (4, 1), (3, 0), (0, 0)]],
+        '6' => &[&[(4, 6), (1, 6), (0, 5), (0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (3, 3), (0, 3)]],
+        '7' => &[&[(0, 6), (4, 6), (1, 0)]],
+        '8' => &[&[(1, 3), (0, 4), (0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (3, 3), (1, 3), (0, 2),
+                   (0, 1), (1, 0), (3, 0), (4, 1), (4, 2), (3, 3)]],
+        '9' => &[&[(4, 3), (1, 3), (0, 4), (0, 5), (1, 6), (3, 6), (4, 5), (4, 1), (3, 0), (0, 0)]],
+        '.' => &[&[(2, 0)]],
+        ',' => &[&[(2, 1), (1, -1)]],
+        ':' => &[&[(2, 4)], &[(2, 1)]],
+        '\'' => &[&[(2, 6), (2, 4)]],
+        '!' => &[&[(2, 6), (2, 2)], &[(2, 0)]],
+        '-' => &[&[(1, 3), (3, 3)]],
+        '+' => &[&[(0, 3), (4, 3)], &[(2, 1), (2, 5)]],
+        '=' => &[&[(0, 2), (4, 2)], &[(0, 4), (4, 4)]],
+        '_' => &[&[(0, 0), (4, 0)]],
+        '/' => &[&[(0, 0), (4, 6)]],
+        '(' => &[&[(3, 6), (2, 5), (2, 1), (3, 0)]],
+        ')' => &[&[(1, 6), (2, 5), (2, 1), (1, 0)]],
+        _ => &[&[(0, 5), (1, 6), (3, 6), (4, 5), (4, 4), (2, 3), (2, 2)], &[(2, 0)]],
+    }
+}
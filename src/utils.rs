@@ -1,28 +1,27 @@
 use byteorder::{ByteOrder, WriteBytesExt, LE};
 use nom::le_i32;
 use nom::IResult;
+use std::io::{self, Read};
 use std::str;
 
 use super::{BestTimes, ElmaError, Time, TimeEntry};
 
 /// Parse top10 lists and return a vector of `TimeEntry`s
 pub fn parse_top10(top10: &[u8]) -> Result<Vec<TimeEntry>, ElmaError> {
+    let mut reader = ByteReader::new(top10);
+    let times = reader.read_i32("top10")?;
+    if !(0..=10).contains(&times) {
+        return Err(ElmaError::InvalidTop10Count(times));
+    }
+
     let mut list: Vec<TimeEntry> = vec![];
-    let times = LE::read_i32(&top10[0..4]);
     for n in 0..times as usize {
-        let time_offset = 4 + n * 4;
-        let time_end = time_offset + 4;
-        let name_1_offset = 44 + n * 15;
-        let name_1_end = name_1_offset + 15;
-        let name_2_offset = 194 + n * 15;
-        let name_2_end = name_2_offset + 15;
-
-        let name_1 = &top10[name_1_offset..name_1_end];
-        let name_2 = &top10[name_2_offset..name_2_end];
-        let time = &top10[time_offset..time_end];
+        let time = reader.at(4 + n * 4).read_i32("top10")?;
+        let name_1 = reader.at(44 + n * 15).read_string(15, "top10")?;
+        let name_2 = reader.at(194 + n * 15).read_string(15, "top10")?;
         list.push(TimeEntry {
-            time: Time(LE::read_i32(time)),
-            names: (trim_string(name_1)?, trim_string(name_2)?),
+            time: Time(time),
+            names: (name_1, name_2),
         });
     }
     Ok(list)
@@ -140,6 +139,155 @@ pub fn string_null_pad(name: &str, pad: usize) -> Result<Vec<u8>, ElmaError> {
     Ok(bytes)
 }
 
+/// Bounds-checked reader over a byte buffer. Reads past the end of the buffer return
+/// `ElmaError::UnexpectedEof` with the offset of the failed read instead of panicking.
+pub(crate) struct ByteReader<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(buffer: &'a [u8]) -> Self {
+        ByteReader { buffer, offset: 0 }
+    }
+
+    /// Returns a reader over the same buffer, positioned at `offset`.
+    pub(crate) fn at(&self, offset: usize) -> Self {
+        ByteReader {
+            buffer: self.buffer,
+            offset,
+        }
+    }
+
+    pub(crate) fn take(
+        &mut self,
+        len: usize,
+        section: &'static str,
+    ) -> Result<&'a [u8], ElmaError> {
+        let end = match self.offset.checked_add(len) {
+            Some(end) if end <= self.buffer.len() => end,
+            _ => {
+                return Err(ElmaError::UnexpectedEof {
+                    offset: self.offset,
+                    section,
+                })
+            }
+        };
+        let bytes = &self.buffer[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    /// Takes `count` records of `size` bytes each.
+    pub(crate) fn take_records(
+        &mut self,
+        count: usize,
+        size: usize,
+        section: &'static str,
+    ) -> Result<&'a [u8], ElmaError> {
+        match count.checked_mul(size) {
+            Some(len) => self.take(len, section),
+            None => Err(ElmaError::UnexpectedEof {
+                offset: self.offset,
+                section,
+            }),
+        }
+    }
+
+    pub(crate) fn read_i32(&mut self, section: &'static str) -> Result<i32, ElmaError> {
+        Ok(LE::read_i32(self.take(4, section)?))
+    }
+
+    pub(crate) fn read_u32(&mut self, section: &'static str) -> Result<u32, ElmaError> {
+        Ok(LE::read_u32(self.take(4, section)?))
+    }
+
+    pub(crate) fn read_f64(&mut self, section: &'static str) -> Result<f64, ElmaError> {
+        Ok(LE::read_f64(self.take(8, section)?))
+    }
+
+    pub(crate) fn read_string(
+        &mut self,
+        len: usize,
+        section: &'static str,
+    ) -> Result<String, ElmaError> {
+        trim_string(self.take(len, section)?)
+    }
+}
+
+/// Reader counterpart of `ByteReader` for streams. Keeps track of the bytes consumed so that
+/// reads past the end of the stream return `ElmaError::UnexpectedEof` with their offset.
+pub(crate) struct StreamReader<R> {
+    reader: R,
+    offset: usize,
+}
+
+impl<R: Read> StreamReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        StreamReader { reader, offset: 0 }
+    }
+
+    pub(crate) fn take(&mut self, len: usize, section: &'static str) -> Result<Vec<u8>, ElmaError> {
+        let mut buffer = vec![0; len];
+        match self.reader.read_exact(&mut buffer) {
+            Ok(()) => {
+                self.offset += len;
+                Ok(buffer)
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(ElmaError::UnexpectedEof {
+                    offset: self.offset,
+                    section,
+                })
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Skips over `count` records of `size` bytes each without keeping them.
+    pub(crate) fn skip_records(
+        &mut self,
+        count: usize,
+        size: usize,
+        section: &'static str,
+    ) -> Result<(), ElmaError> {
+        let eof = ElmaError::UnexpectedEof {
+            offset: self.offset,
+            section,
+        };
+        let len = match count.checked_mul(size) {
+            Some(len) => len,
+            None => return Err(eof),
+        };
+        let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())?;
+        if skipped < len as u64 {
+            return Err(eof);
+        }
+        self.offset += len;
+        Ok(())
+    }
+
+    pub(crate) fn read_i32(&mut self, section: &'static str) -> Result<i32, ElmaError> {
+        Ok(LE::read_i32(&self.take(4, section)?))
+    }
+
+    pub(crate) fn read_u32(&mut self, section: &'static str) -> Result<u32, ElmaError> {
+        Ok(LE::read_u32(&self.take(4, section)?))
+    }
+
+    pub(crate) fn read_f64(&mut self, section: &'static str) -> Result<f64, ElmaError> {
+        Ok(LE::read_f64(&self.take(8, section)?))
+    }
+
+    pub(crate) fn read_string(
+        &mut self,
+        len: usize,
+        section: &'static str,
+    ) -> Result<String, ElmaError> {
+        trim_string(&self.take(len, section)?)
+    }
+}
+
 #[cfg_attr(rustfmt, rustfmt_skip)]
 named!(_boolean<bool>,
   map!(le_i32, to_bool)
@@ -0,0 +1,177 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use elma::ascii::*;
+use elma::geometry::{area, contains};
+use elma::lev::*;
+use elma::repair::self_intersects;
+use elma::{ElmaError, Position};
+use rand::Rng;
+
+fn segments_cross(a: Position<f64>, b: Position<f64>, c: Position<f64>, d: Position<f64>) -> bool {
+    let side = |p: Position<f64>, q: Position<f64>, r: Position<f64>| {
+        (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x)
+    };
+    let (d1, d2) = (side(a, b, c), side(a, b, d));
+    let (d3, d4) = (side(c, d, a), side(c, d, b));
+    let overlap = |p: f64, q: f64, r: f64, s: f64| p.min(q) <= r.max(s) && r.min(s) <= p.max(q);
+    d1 * d2 <= 0. && d3 * d4 <= 0. && overlap(a.x, b.x, c.x, d.x) && overlap(a.y, b.y, c.y, d.y)
+}
+
+// No polygon crosses or touches itself or another polygon.
+fn check_polygons(polygons: &[Polygon]) {
+    for (n, polygon) in polygons.iter().enumerate() {
+        assert!(!self_intersects(polygon), "{:?}", polygon);
+        for other in &polygons[n + 1..] {
+            let (p, q) = (&polygon.vertices, &other.vertices);
+            for i in 0..p.len() {
+                for j in 0..q.len() {
+                    assert!(!segments_cross(
+                        p[i],
+                        p[(i + 1) % p.len()],
+                        q[j],
+                        q[(j + 1) % q.len()]
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn room_with_objects() {
+    let level = parse_ascii(
+        r"
+        ##########
+        #S A K ^F#
+        #. v < > #
+        ##########
+        ",
+        &AsciiOptions { cell_size: 2. },
+    )
+    .unwrap();
+    assert_eq!(level.polygons.len(), 1);
+    let vertices = &level.polygons[0].vertices;
+    assert_eq!(vertices.len(), 4);
+    for corner in &[(2., -2.), (18., -2.), (18., -6.), (2., -6.)] {
+        assert!(vertices.contains(&Position::new(corner.0, corner.1)));
+    }
+    assert_eq!(area(vertices), 64.);
+
+    let apple = |gravity| ObjectType::Apple {
+        gravity,
+        animation: 1,
+    };
+    let objects: Vec<_> = level
+        .objects
+        .iter()
+        .map(|o| (o.position.x, o.position.y, o.object_type.clone()))
+        .collect();
+    assert_eq!(
+        objects,
+        vec![
+            (3., -3., ObjectType::Player),
+            (7., -3., apple(GravityDirection::None)),
+            (11., -3., ObjectType::Killer),
+            (15., -3., apple(GravityDirection::Up)),
+            (17., -3., ObjectType::Exit),
+            (7., -5., apple(GravityDirection::Down)),
+            (11., -5., apple(GravityDirection::Left)),
+            (15., -5., apple(GravityDirection::Right)),
+        ]
+    );
+}
+
+#[test]
+fn ground_islands_and_open_edges() {
+    // No border: everything outside the grid is ground. The pillar becomes a second polygon.
+    let level = parse_ascii("S     \n  ##  \n  ##  \n     F", &AsciiOptions::new()).unwrap();
+    assert_eq!(level.polygons.len(), 2);
+    check_polygons(&level.polygons);
+    assert_eq!(area(&level.polygons[0].vertices).abs(), 24.);
+    assert_eq!(area(&level.polygons[1].vertices).abs(), 4.);
+    assert!(!contains(&level.polygons, &Position::new(2.5, -1.5)));
+    assert!(contains(&level.polygons, &Position::new(0.5, -0.5)));
+    assert!(level.check_topology().is_ok());
+}
+
+#[test]
+fn diagonal_cells_stay_apart() {
+    let level = parse_ascii("S#\n#F", &AsciiOptions::new()).unwrap();
+    assert_eq!(level.polygons.len(), 2);
+    check_polygons(&level.polygons);
+    for polygon in &level.polygons {
+        assert_eq!(polygon.vertices.len(), 4);
+        assert!(!polygon.vertices.contains(&Position::new(1., -1.)));
+    }
+
+    // One air region wrapping around to touch itself at a corner.
+    let level = parse_ascii("S   \n # #\n  # \n   F", &AsciiOptions::new()).unwrap();
+    check_polygons(&level.polygons);
+}
+
+#[test]
+fn random_grids() {
+    let mut rng = common::rng();
+    for _ in 0..100 {
+        let (width, height) = (rng.gen_range(2..12), rng.gen_range(1..8));
+        let mut cells: Vec<Vec<char>> = (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| if rng.gen_bool(0.4) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        cells[0][0] = 'S';
+        cells[height - 1][width - 1] = 'F';
+        let grid: Vec<String> = cells.iter().map(|row| row.iter().collect()).collect();
+        let level = parse_ascii(&grid.join("\n"), &AsciiOptions::new()).unwrap();
+
+        check_polygons(&level.polygons);
+        for (y, row) in cells.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let center = Position::new(x as f64 + 0.5, -(y as f64) - 0.5);
+                assert_eq!(
+                    contains(&level.polygons, &center),
+                    cell != '#',
+                    "{:?}",
+                    grid
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn invalid_grids() {
+    assert_eq!(
+        parse_ascii("\n#S F#\n#  x#", &AsciiOptions::new()).err(),
+        Some(ElmaError::InvalidAscii(AsciiError::InvalidCharacter {
+            line: 3,
+            column: 4,
+            character: 'x',
+        }))
+    );
+    assert_eq!(
+        parse_ascii("###\n###", &AsciiOptions::new()).err(),
+        Some(ElmaError::InvalidAscii(AsciiError::NoAir))
+    );
+    assert_eq!(
+        parse_ascii("  \n", &AsciiOptions::new()).err(),
+        Some(ElmaError::InvalidAscii(AsciiError::NoAir))
+    );
+    assert_eq!(
+        parse_ascii("S S F", &AsciiOptions::new()).err(),
+        Some(ElmaError::InvalidAscii(AsciiError::InvalidTopology(
+            TopologyError::InvalidPlayerCount(2)
+        )))
+    );
+    assert_eq!(
+        parse_ascii("S  ", &AsciiOptions::new()).err(),
+        Some(ElmaError::InvalidAscii(AsciiError::InvalidTopology(
+            TopologyError::MissingExit
+        )))
+    );
+}
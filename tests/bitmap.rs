@@ -0,0 +1,179 @@
+extern crate elma;
+
+use elma::bitmap::*;
+use elma::lev::*;
+use elma::ElmaError;
+
+// 20x10 room: a solid border one pixel thick with a 4x2 block in the middle.
+fn room() -> Bitmap {
+    let mut bitmap = Bitmap::new(20, 10);
+    for x in 0..20 {
+        bitmap.set(x, 0, true);
+        bitmap.set(x, 9, true);
+    }
+    for y in 0..10 {
+        bitmap.set(0, y, true);
+        bitmap.set(19, y, true);
+    }
+    for x in 8..12 {
+        for y in 4..6 {
+            bitmap.set(x, y, true);
+        }
+    }
+    bitmap
+}
+
+fn area(polygon: &Polygon) -> f64 {
+    let v = &polygon.vertices;
+    (0..v.len())
+        .map(|i| {
+            let (a, b) = (v[i], v[(i + 1) % v.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        .abs()
+        / 2.
+}
+
+#[test]
+fn parse_all_formats() {
+    let expected = vec![true, false, false, true, true, false, true, false];
+    let plain_pbm = "P1\n# comment\n4 2\n1 0 0 1\n1010\n";
+    let plain_pgm = "P2 4 2 255\n0 255 200 10\n# dark\n127 128 0 255\n";
+    let mut raw_pbm = b"P4\n4 2\n".to_vec();
+    raw_pbm.extend(&[0b1001_0000, 0b1010_1111]);
+    let mut raw_pgm = b"P5 4 2 255\n".to_vec();
+    raw_pgm.extend(&[0, 255, 200, 10, 127, 128, 0, 255]);
+    let mut wide_pgm = b"P5 4 2 1000\n".to_vec();
+    for value in &[0u16, 1000, 600, 100, 499, 500, 0, 999] {
+        wide_pgm.push((value >> 8) as u8);
+        wide_pgm.push(*value as u8);
+    }
+
+    for buffer in &[
+        plain_pbm.as_bytes().to_vec(),
+        plain_pgm.as_bytes().to_vec(),
+        raw_pbm,
+        raw_pgm,
+        wide_pgm,
+    ] {
+        let bitmap = Bitmap::from_bytes(buffer).unwrap();
+        assert_eq!((bitmap.width, bitmap.height), (4, 2));
+        assert_eq!(bitmap.pixels, expected);
+    }
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        Bitmap::from_bytes("P6 1 1 255\n\0\0\0"),
+        Err(ElmaError::InvalidBitmap(BitmapError::UnsupportedFormat(
+            b"P6".to_vec()
+        )))
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P1 0 3\n"),
+        Err(ElmaError::InvalidBitmap(BitmapError::InvalidHeader))
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P2 2 2\n"),
+        Err(ElmaError::InvalidBitmap(BitmapError::InvalidHeader))
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P1 2 2\n1 0 2 1"),
+        Err(ElmaError::InvalidBitmap(BitmapError::InvalidPixel(11)))
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P1 2 2\n1 0 1"),
+        Err(ElmaError::UnexpectedEof {
+            offset: 12,
+            section: "pixels",
+        })
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P4 16 3\n\0\0\0\0\0"),
+        Err(ElmaError::UnexpectedEof {
+            offset: 12,
+            section: "pixels",
+        })
+    );
+    assert_eq!(
+        Bitmap::from_bytes("P5 99999999 99999999 255\n\0"),
+        Err(ElmaError::UnexpectedEof {
+            offset: 25,
+            section: "pixels",
+        })
+    );
+}
+
+#[test]
+fn trace_room() {
+    let options = TraceOptions {
+        max_size: 40.,
+        ..TraceOptions::new()
+    };
+    let polygons = room().trace(&options);
+    assert_eq!(polygons.len(), 2);
+    for polygon in &polygons {
+        assert_eq!(polygon.vertices.len(), 4);
+        assert!(!polygon.grass);
+    }
+    // Outlines run between the centres of ground and air pixels, two level units apart.
+    let mut areas: Vec<f64> = polygons.iter().map(area).collect();
+    areas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert!(areas[0] > 3. * 1. * 4. && areas[0] < 4. * 2. * 4.);
+    assert!(areas[1] > 17. * 7. * 4. && areas[1] < 18. * 8. * 4.);
+    for vertex in polygons.iter().flat_map(|p| &p.vertices) {
+        assert!(vertex.x > 0. && vertex.x < 40. && vertex.y < 0. && vertex.y > -20.);
+    }
+}
+
+#[test]
+fn trace_fits_max_size() {
+    let options = TraceOptions::new();
+    let mut bitmap = Bitmap::new(300, 100);
+    for x in 0..300 {
+        bitmap.set(x, 99, true);
+    }
+    let polygons = bitmap.trace(&options);
+    assert_eq!(polygons.len(), 1);
+    for vertex in &polygons[0].vertices {
+        assert!(vertex.x >= 0. && vertex.x <= 188.);
+        assert!(vertex.y <= 0. && vertex.y >= -188. / 3.);
+    }
+}
+
+#[test]
+fn trace_drops_specks_and_inverts() {
+    let mut bitmap = Bitmap::new(10, 10);
+    bitmap.set(3, 3, true);
+    assert_eq!(bitmap.trace(&TraceOptions::new()).len(), 1);
+    let keep = TraceOptions {
+        min_area: 0.,
+        tolerance: 0.1,
+        ..TraceOptions::new()
+    };
+    assert_eq!(bitmap.trace(&keep).len(), 2);
+
+    let inverted = TraceOptions {
+        invert: true,
+        ..TraceOptions::new()
+    };
+    assert_eq!(
+        bitmap.to_level(&inverted),
+        Err(ElmaError::InvalidBitmap(BitmapError::NoAir))
+    );
+}
+
+#[test]
+fn room_to_level() {
+    let level = room().to_level(&TraceOptions::new()).unwrap();
+    assert!(level.check_topology().is_ok());
+    assert_eq!(level.polygons.len(), 2);
+    assert_eq!(level.objects.len(), 2);
+    let player = &level.objects[0];
+    let exit = &level.objects[1];
+    assert_eq!(player.object_type, ObjectType::Player);
+    assert_eq!(exit.object_type, ObjectType::Exit);
+    assert!(player.position.x < exit.position.x);
+}
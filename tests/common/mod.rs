@@ -0,0 +1,44 @@
+// Helpers shared by the integration tests. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use elma::lev::Polygon;
+use elma::Position;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Seeded random number generator, so that failures can be reproduced.
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(0x656c_6d61)
+}
+
+/// Polygon through the given vertices.
+pub fn polygon(grass: bool, vertices: &[(f64, f64)]) -> Polygon {
+    Polygon {
+        grass,
+        vertices: vertices.iter().map(|&(x, y)| Position::new(x, y)).collect(),
+    }
+}
+
+/// Checks that parsing damaged copies of a valid file returns instead of panicking: every
+/// truncation of it must fail, and `corruptions` copies with a few random bytes changed must
+/// parse or fail without a panic.
+pub fn check_damaged<T, E, F>(original: &[u8], corruptions: usize, parse: F)
+where
+    F: Fn(&[u8]) -> Result<T, E>,
+{
+    // Large files are cut at fewer places.
+    let step = original.len() / 5000 + 1;
+    for len in (0..original.len()).step_by(step) {
+        assert!(parse(&original[..len]).is_err(), "truncated to {}", len);
+    }
+
+    let mut rng = rng();
+    for _ in 0..corruptions {
+        let mut buffer = original.to_vec();
+        for _ in 0..rng.gen_range(1..8) {
+            let index = rng.gen_range(0..buffer.len());
+            buffer[index] = rng.gen();
+        }
+        let _ = parse(&buffer);
+    }
+}
@@ -0,0 +1,188 @@
+extern crate elma;
+
+use elma::dxf::*;
+use elma::lev::*;
+use elma::{ElmaError, Position};
+
+fn dxf(entities: &[&str]) -> String {
+    let mut source = String::from("0\nSECTION\n2\nENTITIES\n");
+    for entity in entities {
+        source.push_str(entity);
+    }
+    source.push_str("0\nENDSEC\n0\nEOF\n");
+    source
+}
+
+#[test]
+fn round_trip() {
+    let mut level = Level::new();
+    level.polygons.push(Polygon {
+        grass: true,
+        vertices: vec![
+            Position::new(0.1, 0.2),
+            Position::new(9.75, 0.2),
+            Position::new(9.75, -1. / 3.),
+        ],
+    });
+    for (n, &(gravity, animation)) in [
+        (GravityDirection::None, 1),
+        (GravityDirection::Up, 1),
+        (GravityDirection::Down, 9),
+        (GravityDirection::Left, 2),
+        (GravityDirection::Right, 1),
+    ]
+    .iter()
+    .enumerate()
+    {
+        level.objects.push(Object {
+            position: Position::new(n as f64 + 0.5, 3.25),
+            object_type: ObjectType::Apple { gravity, animation },
+        });
+    }
+    level.objects.push(Object {
+        position: Position::new(1e-7, -123456.789),
+        object_type: ObjectType::Killer,
+    });
+
+    let source = export_dxf(&level);
+    assert!(source.contains("  8\nAPPLE_DOWN_9\n"));
+    assert!(source.contains("  8\nGRASS\n"));
+    let import = import_dxf(&source, &DxfOptions::new()).unwrap();
+    assert_eq!(import.polygons, level.polygons);
+    assert_eq!(import.objects, level.objects);
+
+    let level = import.into_level();
+    assert!(level.check_topology().is_ok());
+}
+
+#[test]
+fn polylines_and_layers() {
+    let source = dxf(&[
+        // Lightweight polyline on a lowercase grass layer, left open.
+        "0\nLWPOLYLINE\n8\ngrass\n90\n3\n70\n0\n10\n0\n20\n0\n10\n4\n20\n0\n10\n4\n20\n2\n",
+        // Old-style polyline with vertices.
+        "0\nPOLYLINE\n8\nWalls\n66\n1\n70\n1\n",
+        "0\nVERTEX\n8\nWalls\n10\n10\n20\n10\n",
+        "0\nVERTEX\n8\nWalls\n10\n12\n20\n10\n",
+        "0\nVERTEX\n8\nWalls\n10\n12\n20\n12\n",
+        "0\nSEQEND\n",
+        // 3D polyline, not a level outline.
+        "0\nPOLYLINE\n8\n0\n70\n8\n",
+        "0\nVERTEX\n10\n0\n20\n0\n0\nVERTEX\n10\n1\n20\n0\n0\nVERTEX\n10\n1\n20\n1\n",
+        "0\nSEQEND\n",
+        // Objects as points and circles.
+        "0\nPOINT\n8\nStart\n10\n1\n20\n1\n",
+        "0\nCIRCLE\n8\nFLOWER\n10\n3\n20\n1\n40\n0.4\n",
+        "0\nCIRCLE\n8\napple_left_4\n10\n2\n20\n1\n40\n0.4\n",
+        // Unsupported entity.
+        "0\nLINE\n8\n0\n10\n0\n20\n0\n11\n5\n21\n5\n",
+    ]);
+    let import = import_dxf(&source, &DxfOptions::new()).unwrap();
+    assert_eq!(
+        import.polygons,
+        vec![
+            Polygon {
+                grass: true,
+                vertices: vec![
+                    Position::new(0., 0.),
+                    Position::new(4., 0.),
+                    Position::new(4., 2.),
+                ],
+            },
+            Polygon {
+                grass: false,
+                vertices: vec![
+                    Position::new(10., 10.),
+                    Position::new(12., 10.),
+                    Position::new(12., 12.),
+                ],
+            },
+        ]
+    );
+    assert_eq!(
+        import.objects,
+        vec![
+            Object {
+                position: Position::new(1., 1.),
+                object_type: ObjectType::Player,
+            },
+            Object {
+                position: Position::new(3., 1.),
+                object_type: ObjectType::Exit,
+            },
+            Object {
+                position: Position::new(2., 1.),
+                object_type: ObjectType::Apple {
+                    gravity: GravityDirection::Left,
+                    animation: 4,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn arcs_and_circles() {
+    // A half-disc: counter-clockwise arc from (2, 0) to (-2, 0), then straight back.
+    let source = dxf(&[
+        "0\nLWPOLYLINE\n8\n0\n70\n1\n10\n2\n20\n0\n42\n1\n10\n-2\n20\n0\n",
+        "0\nCIRCLE\n8\nRocks\n10\n20\n20\n0\n40\n3\n",
+    ]);
+    let options = DxfOptions {
+        scale: 2.,
+        tolerance: 0.01,
+    };
+    let import = import_dxf(&source, &options).unwrap();
+    assert_eq!(import.polygons.len(), 2);
+    assert!(import.objects.is_empty());
+
+    let half = &import.polygons[0].vertices;
+    assert!(half.len() > 10);
+    assert_eq!(half[0], Position::new(4., 0.));
+    for vertex in &half[1..half.len() - 1] {
+        assert!((vertex.x.hypot(vertex.y) - 4.).abs() < 1e-9);
+        assert!(vertex.y > 0.);
+    }
+    assert_eq!(half[half.len() - 1], Position::new(-4., 0.));
+
+    let circle = &import.polygons[1].vertices;
+    for (n, vertex) in circle.iter().enumerate() {
+        assert!(((vertex.x - 40.).hypot(vertex.y) - 6.).abs() < 1e-9);
+        // Edge midpoints stay within the tolerance of the circle.
+        let next = circle[(n + 1) % circle.len()];
+        let mid = Position::new((vertex.x + next.x) / 2., (vertex.y + next.y) / 2.);
+        assert!(6. - (mid.x - 40.).hypot(mid.y) <= 0.01);
+    }
+}
+
+#[test]
+fn other_sections_are_ignored() {
+    let source = "  0\r\nSECTION\r\n  2\r\nBLOCKS\r\n  0\r\nCIRCLE\r\n  8\r\nPLAYER\r\n 10\r\n0\r\n 20\r\n0\r\n\
+                  40\r\n1\r\n  0\r\nENDSEC\r\n  0\r\nSECTION\r\n  2\r\nENTITIES\r\n  0\r\nPOINT\r\n\
+                  8\r\nKILLER\r\n 10\r\n5\r\n 20\r\n6\r\n  0\r\nENDSEC\r\n  0\r\nEOF\r\n\r\n";
+    let import = import_dxf(source, &DxfOptions::new()).unwrap();
+    assert!(import.polygons.is_empty());
+    assert_eq!(
+        import.objects,
+        vec![Object {
+            position: Position::new(5., 6.),
+            object_type: ObjectType::Killer,
+        }]
+    );
+}
+
+#[test]
+fn invalid_files() {
+    assert_eq!(
+        import_dxf("0\nSECTION\nx\nENTITIES\n", &DxfOptions::new()),
+        Err(ElmaError::InvalidDxf(DxfError::InvalidGroupCode(3)))
+    );
+    assert_eq!(
+        import_dxf(&dxf(&["0\nPOINT\n8\nEXIT\n10\none\n"]), &DxfOptions::new()),
+        Err(ElmaError::InvalidDxf(DxfError::InvalidNumber(10)))
+    );
+    assert_eq!(
+        import_dxf("0\nSECTION\n2", &DxfOptions::new()),
+        Err(ElmaError::InvalidDxf(DxfError::MissingValue(3)))
+    );
+}
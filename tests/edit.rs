@@ -0,0 +1,376 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use elma::edit::*;
+use elma::lev::*;
+use elma::Position;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+type Snapshot = (
+    String,
+    String,
+    String,
+    String,
+    Vec<Polygon>,
+    Vec<Object>,
+    Vec<Picture>,
+);
+
+fn snapshot(level: &Level) -> Snapshot {
+    (
+        level.title.clone(),
+        level.lgr.clone(),
+        level.ground.clone(),
+        level.sky.clone(),
+        level.polygons.clone(),
+        level.objects.clone(),
+        level.pictures.clone(),
+    )
+}
+
+fn random_position(rng: &mut StdRng) -> Position<f64> {
+    Position::new(rng.gen_range(-50. ..50.), rng.gen_range(-50. ..50.))
+}
+
+// Random edit that is valid for the level, as long as the level has a polygon and an apple.
+fn random_edit(rng: &mut StdRng, level: &Level) -> Edit {
+    let polygon = rng.gen_range(0..level.polygons.len());
+    let vertices = level.polygons[polygon].vertices.len();
+    let objects = level.objects.len();
+    let pictures = level.pictures.len();
+    match rng.gen_range(0..16) {
+        0 => Edit::InsertVertex {
+            polygon,
+            vertex: rng.gen_range(0..=vertices),
+            position: random_position(rng),
+        },
+        1 => Edit::MoveVertex {
+            polygon,
+            vertex: rng.gen_range(0..vertices),
+            position: random_position(rng),
+        },
+        2 if vertices > 3 => Edit::RemoveVertex {
+            polygon,
+            vertex: rng.gen_range(0..vertices),
+        },
+        3 => Edit::InsertPolygon {
+            index: rng.gen_range(0..=level.polygons.len()),
+            polygon: Polygon {
+                grass: rng.gen(),
+                vertices: (0..3).map(|_| random_position(rng)).collect(),
+            },
+        },
+        4 if level.polygons.len() > 1 => Edit::RemovePolygon {
+            index: rng.gen_range(0..level.polygons.len()),
+        },
+        // The player, exit and apple keep their indices.
+        5 => Edit::InsertObject {
+            index: rng.gen_range(3..=objects),
+            object: Object {
+                position: random_position(rng),
+                object_type: ObjectType::Killer,
+            },
+        },
+        6 => Edit::MoveObject {
+            index: rng.gen_range(0..objects),
+            position: random_position(rng),
+        },
+        7 if objects > 3 => Edit::RemoveObject {
+            index: rng.gen_range(3..objects),
+        },
+        8 if objects > 3 => Edit::SetObjectType {
+            index: rng.gen_range(3..objects),
+            object_type: ObjectType::Exit,
+        },
+        9 => Edit::SetGravity {
+            index: 2,
+            gravity: GravityDirection::Left,
+        },
+        10 => Edit::InsertPicture {
+            index: rng.gen_range(0..=pictures),
+            picture: Picture {
+                position: random_position(rng),
+                ..Picture::new()
+            },
+        },
+        11 if pictures > 0 => Edit::MovePicture {
+            index: rng.gen_range(0..pictures),
+            position: random_position(rng),
+        },
+        12 if pictures > 0 => Edit::RemovePicture {
+            index: rng.gen_range(0..pictures),
+        },
+        13 => Edit::SetTitle(format!("title {}", rng.gen::<u8>())),
+        14 => Edit::SetLgr(format!("lgr{}", rng.gen::<u8>())),
+        _ => {
+            if rng.gen() {
+                Edit::SetGround("stone".into())
+            } else {
+                Edit::SetSky("cloud".into())
+            }
+        }
+    }
+}
+
+fn level_with_apple() -> Level {
+    let mut level = Level::new();
+    level.objects.push(Object {
+        position: Position::new(5., 1.),
+        object_type: ObjectType::Apple {
+            gravity: GravityDirection::Up,
+            animation: 4,
+        },
+    });
+    level
+}
+
+#[test]
+fn inverse_restores_level() {
+    let mut rng = common::rng();
+    let mut level = level_with_apple();
+    for _ in 0..500 {
+        let before = snapshot(&level);
+        let edit = random_edit(&mut rng, &level);
+        let inverse = edit.clone().apply(&mut level).unwrap();
+        let after = snapshot(&level);
+        let redo = inverse.apply(&mut level).unwrap();
+        assert_eq!(snapshot(&level), before, "{:?}", edit);
+        assert_eq!(redo, edit);
+        redo.apply(&mut level).unwrap();
+        assert_eq!(snapshot(&level), after);
+    }
+}
+
+#[test]
+fn move_polygon_and_back() {
+    let mut level = level_with_apple();
+    let before = snapshot(&level);
+    let edit = Edit::MovePolygon {
+        index: 0,
+        offset: Position::new(2.5, -1.),
+    };
+    let undo = edit.clone().apply(&mut level).unwrap();
+    assert_eq!(
+        undo,
+        Edit::MovePolygon {
+            index: 0,
+            offset: Position::new(-2.5, 1.),
+        }
+    );
+    for (moved, original) in level.polygons[0]
+        .vertices
+        .iter()
+        .zip(&Level::new().polygons[0].vertices)
+    {
+        assert_eq!(*moved, Position::new(original.x + 2.5, original.y - 1.));
+    }
+    let after = snapshot(&level);
+    assert_eq!(undo.apply(&mut level).unwrap(), edit);
+    assert_eq!(snapshot(&level), before);
+    edit.apply(&mut level).unwrap();
+    assert_eq!(snapshot(&level), after);
+}
+
+#[test]
+fn invalid_edits() {
+    let mut level = level_with_apple();
+    let before = snapshot(&level);
+    let cases = vec![
+        (
+            Edit::InsertVertex {
+                polygon: 0,
+                vertex: 5,
+                position: Position::new(0., 0.),
+            },
+            EditError::NoVertex {
+                polygon: 0,
+                vertex: 5,
+            },
+        ),
+        (
+            Edit::RemoveVertex {
+                polygon: 1,
+                vertex: 0,
+            },
+            EditError::NoPolygon(1),
+        ),
+        (Edit::RemovePolygon { index: 1 }, EditError::NoPolygon(1)),
+        (
+            Edit::MovePolygon {
+                index: 1,
+                offset: Position::new(1., 1.),
+            },
+            EditError::NoPolygon(1),
+        ),
+        (
+            Edit::MoveObject {
+                index: 3,
+                position: Position::new(0., 0.),
+            },
+            EditError::NoObject(3),
+        ),
+        (
+            Edit::SetGravity {
+                index: 0,
+                gravity: GravityDirection::Down,
+            },
+            EditError::NotAnApple(0),
+        ),
+        (Edit::RemovePicture { index: 0 }, EditError::NoPicture(0)),
+    ];
+    for (edit, error) in cases {
+        assert_eq!(edit.apply(&mut level), Err(error));
+        assert_eq!(snapshot(&level), before);
+    }
+}
+
+#[test]
+fn failed_group_is_rolled_back() {
+    let mut level = level_with_apple();
+    let before = snapshot(&level);
+    let group = Edit::Group(vec![
+        Edit::SetTitle("changed".into()),
+        Edit::RemoveObject { index: 0 },
+        Edit::SetGravity {
+            index: 1,
+            gravity: GravityDirection::Right,
+        },
+        Edit::RemoveVertex {
+            polygon: 0,
+            vertex: 0,
+        },
+    ]);
+    assert_eq!(
+        group.apply(&mut level),
+        Ok(Edit::Group(vec![
+            Edit::InsertVertex {
+                polygon: 0,
+                vertex: 0,
+                position: before.4[0].vertices[0],
+            },
+            Edit::SetGravity {
+                index: 1,
+                gravity: GravityDirection::Up,
+            },
+            Edit::InsertObject {
+                index: 0,
+                object: before.5[0].clone(),
+            },
+            Edit::SetTitle(before.0.clone()),
+        ]))
+    );
+
+    let mut level = level_with_apple();
+    let group = Edit::Group(vec![
+        Edit::SetTitle("changed".into()),
+        Edit::RemoveObject { index: 0 },
+        Edit::SetGravity {
+            index: 0,
+            gravity: GravityDirection::Right,
+        },
+    ]);
+    assert_eq!(group.apply(&mut level), Err(EditError::NotAnApple(0)));
+    assert_eq!(snapshot(&level), before);
+}
+
+#[test]
+fn history_undo_redo() {
+    let mut rng = common::rng();
+    let mut level = level_with_apple();
+    let mut history = History::new();
+    let mut snapshots = vec![snapshot(&level)];
+    assert!(!history.can_undo());
+    assert_eq!(history.undo(&mut level), Ok(false));
+
+    // Single edits and nested groups.
+    for step in 0..40 {
+        if step % 5 == 0 {
+            history.begin_group();
+            for _ in 0..3 {
+                let edit = random_edit(&mut rng, &level);
+                history.apply(&mut level, edit).unwrap();
+            }
+            history.begin_group();
+            history.end_group();
+            history.begin_group();
+            let edit = random_edit(&mut rng, &level);
+            history.apply(&mut level, edit).unwrap();
+            history.end_group();
+            history.end_group();
+        } else {
+            let edit = random_edit(&mut rng, &level);
+            history.apply(&mut level, edit).unwrap();
+        }
+        snapshots.push(snapshot(&level));
+    }
+
+    for expected in snapshots.iter().rev().skip(1) {
+        assert!(history.undo(&mut level).unwrap());
+        assert_eq!(&snapshot(&level), expected);
+    }
+    assert!(!history.can_undo());
+    assert_eq!(history.undo(&mut level), Ok(false));
+
+    for expected in snapshots.iter().skip(1).take(10) {
+        assert!(history.redo(&mut level).unwrap());
+        assert_eq!(&snapshot(&level), expected);
+    }
+    assert!(history.can_redo());
+
+    // A new edit drops the rest of the redo list.
+    history
+        .apply(&mut level, Edit::SetTitle("new".into()))
+        .unwrap();
+    assert!(!history.can_redo());
+    assert_eq!(history.redo(&mut level), Ok(false));
+    assert!(history.undo(&mut level).unwrap());
+    assert_eq!(snapshot(&level), snapshots[10]);
+
+    history.clear();
+    assert!(!history.can_undo() && !history.can_redo());
+}
+
+#[test]
+fn undo_ends_open_groups() {
+    let mut level = level_with_apple();
+    let before = snapshot(&level);
+    let mut history = History::new();
+    history.begin_group();
+    history
+        .apply(&mut level, Edit::SetSky("night".into()))
+        .unwrap();
+    history
+        .apply(&mut level, Edit::RemoveObject { index: 2 })
+        .unwrap();
+    assert!(history.can_undo());
+    assert!(history.undo(&mut level).unwrap());
+    assert_eq!(snapshot(&level), before);
+    assert!(history.redo(&mut level).unwrap());
+    assert_eq!(level.sky, "night");
+    assert_eq!(level.objects.len(), 2);
+}
+
+#[test]
+fn failed_undo_is_kept() {
+    let mut level = level_with_apple();
+    let mut history = History::new();
+    history
+        .apply(
+            &mut level,
+            Edit::MoveObject {
+                index: 2,
+                position: Position::new(1., 1.),
+            },
+        )
+        .unwrap();
+    // The level is changed behind the history's back.
+    level.objects.pop();
+    assert_eq!(history.undo(&mut level), Err(EditError::NoObject(2)));
+    assert!(history.can_undo());
+    level.objects.push(Object::new());
+    assert!(history.undo(&mut level).unwrap());
+    assert_eq!(level.objects[2].position, Position::new(5., 1.));
+}
@@ -0,0 +1,126 @@
+extern crate elma;
+
+use elma::geometry::*;
+use elma::lev::*;
+use elma::Position;
+
+fn rectangle(x: f64, y: f64, width: f64, height: f64) -> Polygon {
+    Polygon {
+        grass: false,
+        vertices: vec![
+            Position::new(x, y),
+            Position::new(x + width, y),
+            Position::new(x + width, y + height),
+            Position::new(x, y + height),
+        ],
+    }
+}
+
+fn total_area(polygons: &[Polygon]) -> f64 {
+    // Even-odd nesting: holes have opposite orientation from their outer polygon.
+    polygons
+        .iter()
+        .map(|p| area(&p.vertices))
+        .sum::<f64>()
+        .abs()
+}
+
+#[test]
+fn union_overlapping_squares() {
+    let result = union(&[rectangle(0., 0., 2., 2.)], &[rectangle(1., 1., 2., 2.)]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].vertices.len(), 8);
+    assert!((total_area(&result) - 7.).abs() < 1e-9);
+}
+
+#[test]
+fn union_shared_edge_merges() {
+    let result = union(&[rectangle(0., 0., 1., 1.)], &[rectangle(1., 0., 1., 1.)]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].vertices.len(), 4);
+    assert!((total_area(&result) - 2.).abs() < 1e-9);
+}
+
+#[test]
+fn union_disjoint_keeps_both() {
+    let result = union(&[rectangle(0., 0., 1., 1.)], &[rectangle(5., 5., 1., 1.)]);
+    assert_eq!(result.len(), 2);
+}
+
+#[test]
+fn intersection_overlapping_squares() {
+    let result = intersection(&[rectangle(0., 0., 2., 2.)], &[rectangle(1., 1., 2., 2.)]);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].vertices.len(), 4);
+    assert!((total_area(&result) - 1.).abs() < 1e-9);
+    assert!(contains(&result, &Position::new(1.5, 1.5)));
+}
+
+#[test]
+fn difference_carves_hole() {
+    let result = difference(&[rectangle(0., 0., 10., 10.)], &[rectangle(2., 2., 3., 3.)]);
+    assert_eq!(result.len(), 2);
+    assert!((total_area(&result) - 91.).abs() < 1e-9);
+    assert!(!contains(&result, &Position::new(3., 3.)));
+    assert!(contains(&result, &Position::new(1., 1.)));
+}
+
+#[test]
+fn difference_tunnel_splits_ground() {
+    let result = difference(&[rectangle(0., 0., 10., 4.)], &[rectangle(4., -1., 2., 6.)]);
+    assert_eq!(result.len(), 2);
+    assert!((total_area(&result) - 32.).abs() < 1e-9);
+}
+
+#[test]
+fn boolean_respects_even_odd_input() {
+    // Subject already has a hole; filling part of it with the clip set.
+    let subject = vec![rectangle(0., 0., 10., 10.), rectangle(2., 2., 6., 6.)];
+    let result = union(&subject, &[rectangle(4., 4., 2., 2.)]);
+    assert!((total_area(&result) - 68.).abs() < 1e-9);
+    assert!(contains(&result, &Position::new(5., 5.)));
+    assert!(!contains(&result, &Position::new(3., 3.)));
+}
+
+#[test]
+fn boolean_ignores_grass() {
+    let mut grass = rectangle(0., 0., 5., 5.);
+    grass.grass = true;
+    let result = boolean(&[rectangle(0., 0., 1., 1.)], &[grass], BooleanOp::Xor);
+    assert_eq!(result.len(), 1);
+    assert!((total_area(&result) - 1.).abs() < 1e-9);
+}
+
+#[test]
+fn union_nearly_coincident_edges() {
+    // Edges a rounding error apart are treated as the same edge.
+    let a = Polygon {
+        grass: false,
+        vertices: vec![
+            Position::new(0.8, -0.44999999999999996),
+            Position::new(1.2, -0.4499999999999999),
+            Position::new(1.2, 0.45),
+            Position::new(0.8, 0.45),
+        ],
+    };
+    let result = union(&[a], &[rectangle(1., -0.45, 2., 0.9)]);
+    assert_eq!(result.len(), 1);
+    assert!((total_area(&result) - 0.9 * 2.2).abs() < 1e-9);
+}
+
+#[test]
+fn union_with_unbounded_coordinates() {
+    // Damaged level files can hold infinite and overflowing coordinates.
+    let broken = Polygon {
+        grass: false,
+        vertices: vec![
+            Position::new(-4.67, -9.70),
+            Position::new(-1e308, -7.46),
+            Position::new(-9.55, -6.48),
+            Position::new(-6.95, -8.75),
+            Position::new(8.90, -6.),
+            Position::new(f64::NEG_INFINITY, -4.21),
+        ],
+    };
+    union(&[broken], &[rectangle(0., 0., 2., 2.)]);
+}
@@ -0,0 +1,122 @@
+extern crate elma;
+
+use elma::grass::*;
+use elma::lev::*;
+use elma::Position;
+
+fn distance(a: &Position<f64>, b: &Position<f64>) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn longest_edge(polygon: &Polygon) -> usize {
+    let n = polygon.vertices.len();
+    (0..n)
+        .max_by(|&a, &b| {
+            let a = distance(&polygon.vertices[a], &polygon.vertices[(a + 1) % n]);
+            let b = distance(&polygon.vertices[b], &polygon.vertices[(b + 1) % n]);
+            a.partial_cmp(&b).unwrap()
+        })
+        .unwrap()
+}
+
+#[test]
+fn grass_on_default_level_floor() {
+    let level = Level::new();
+    let grass = generate_grass(&level, &GrassOptions::new());
+    assert_eq!(grass.len(), 1);
+    let polygon = &grass[0];
+    assert!(polygon.grass);
+    assert_eq!(polygon.vertices.len(), 4);
+    assert_eq!(polygon.vertices[0], Position::new(0., 0.));
+    assert_eq!(polygon.vertices[1], Position::new(10., 0.));
+    assert!(polygon.vertices[2].y < -0.49);
+    // Closing bottom edge is the longest one.
+    assert_eq!(longest_edge(polygon), 2);
+}
+
+#[test]
+fn grass_follows_hill_and_skips_steep_edges() {
+    let mut level = Level::new();
+    level.polygons[0].vertices = vec![
+        Position::new(0., 0.),
+        Position::new(4., 0.),
+        Position::new(5., 1.),
+        Position::new(6., 0.),
+        Position::new(8., 0.),
+        Position::new(8.5, 5.),
+        Position::new(12., 5.),
+        Position::new(12., 10.),
+        Position::new(0., 10.),
+    ];
+    let grass = generate_grass(&level, &GrassOptions::new());
+    assert_eq!(grass.len(), 2);
+
+    let hill = &grass[0];
+    assert_eq!(hill.vertices[..5], level.polygons[0].vertices[..5]);
+    assert_eq!(longest_edge(hill), 5);
+
+    let plateau = &grass[1];
+    assert_eq!(plateau.vertices[0], Position::new(8.5, 5.));
+    assert_eq!(plateau.vertices[1], Position::new(12., 5.));
+    assert_eq!(longest_edge(plateau), 2);
+}
+
+#[test]
+fn grass_respects_max_slope() {
+    let mut level = Level::new();
+    level.polygons[0].vertices = vec![
+        Position::new(0., 0.),
+        Position::new(4., 0.),
+        Position::new(10., 3.),
+        Position::new(10., 10.),
+        Position::new(0., 10.),
+    ];
+    let options = GrassOptions {
+        max_slope: 0.,
+        ..GrassOptions::new()
+    };
+    let grass = generate_grass(&level, &options);
+    assert_eq!(grass.len(), 1);
+    assert_eq!(grass[0].vertices.len(), 4);
+
+    let grass = generate_grass(&level, &GrassOptions::new());
+    assert_eq!(grass.len(), 1);
+    assert_eq!(grass[0].vertices.len(), 5);
+    assert_eq!(grass[0].vertices[2], Position::new(10., 3.));
+}
+
+#[test]
+fn grass_on_single_sloped_edge() {
+    let mut level = Level::new();
+    level.polygons[0].vertices = vec![
+        Position::new(0., 0.),
+        Position::new(10., 3.),
+        Position::new(10., 10.),
+        Position::new(0., 10.),
+    ];
+    let grass = generate_grass(&level, &GrassOptions::new());
+    assert_eq!(grass.len(), 1);
+    // The bottom edge is widened to stay longer than the sloped edge.
+    assert_eq!(longest_edge(&grass[0]), 2);
+    assert!(grass[0].vertices[2].x > 10.);
+    assert!(grass[0].vertices[3].x < 0.);
+}
+
+#[test]
+fn grass_inside_nested_ground() {
+    let mut level = Level::new();
+    // Ground block floating in the air, with its top edge facing up.
+    level.polygons.push(Polygon {
+        grass: false,
+        vertices: vec![
+            Position::new(3., 3.),
+            Position::new(3., 4.),
+            Position::new(6., 4.),
+            Position::new(6., 3.),
+        ],
+    });
+    let grass = generate_grass(&level, &GrassOptions::new());
+    assert_eq!(grass.len(), 2);
+    assert_eq!(grass[1].vertices[0], Position::new(3., 4.));
+    assert_eq!(grass[1].vertices[1], Position::new(6., 4.));
+}
@@ -1,6 +1,8 @@
 extern crate elma;
 extern crate rand;
 
+mod common;
+
 use elma::constants::TOP10_SIZE;
 use elma::lev::*;
 use elma::{BestTimes, Clip, ElmaError, Position, TimeEntry, Version};
@@ -266,17 +268,18 @@ fn load_valid_level_1() {
         level.pictures,
         vec![
             Picture {
-                name: String::from("barrel"),
-                texture: String::new(),
-                mask: String::new(),
+                kind: PictureKind::Normal {
+                    name: String::from("barrel"),
+                },
                 position: Position::new(-19.37674118849727_f64, -0.895119783101471_f64),
                 distance: 380,
                 clip: Clip::Sky,
             },
             Picture {
-                name: String::new(),
-                texture: String::from("stone1"),
-                mask: String::from("maskbig"),
+                kind: PictureKind::Texture {
+                    texture: String::from("stone1"),
+                    mask: String::from("maskbig"),
+                },
                 position: Position::new(-24.465394017511894_f64, 3.964829547979911_f64),
                 distance: 750,
                 clip: Clip::Sky,
@@ -406,3 +409,169 @@ fn is_apple() {
     );
     assert_eq!(true, lev.objects[2].is_apple());
 }
+
+#[test]
+fn peek_level_header() {
+    let level = Level::load("tests/assets/levels/test_2.lev").unwrap();
+    let header = Level::peek("tests/assets/levels/test_2.lev").unwrap();
+    assert_eq!(header.version, level.version);
+    assert_eq!(header.link, level.link);
+    assert_eq!(header.integrity, level.integrity);
+    assert_eq!(header.title, level.title);
+    assert_eq!(header.lgr, level.lgr);
+    assert_eq!(header.ground, level.ground);
+    assert_eq!(header.sky, level.sky);
+    assert_eq!(header.polygon_count, 5);
+    assert_eq!(header.object_count, 17);
+    assert_eq!(header.picture_count, 3);
+}
+
+#[test]
+fn peek_level_header_from_bytes() {
+    let buffer = fs::read("tests/assets/levels/test_1.lev").unwrap();
+    let header = LevelHeader::from_bytes(&buffer).unwrap();
+    assert_eq!(header.title, "Rust test");
+    assert_eq!(header.polygon_count, 2);
+    // Truncated inside the first polygon's vertices, and inside the LGR name.
+    assert_eq!(
+        LevelHeader::from_bytes(&buffer[..200]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: 146,
+            section: "polygons",
+        }
+    );
+    assert_eq!(
+        LevelHeader::from_bytes(&buffer[..100]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: 94,
+            section: "header",
+        }
+    );
+    assert_eq!(
+        LevelHeader::from_bytes(b"POT06").unwrap_err(),
+        ElmaError::AcrossUnsupported
+    );
+}
+
+#[test]
+fn read_from_and_write_to_level() {
+    let file = fs::File::open("tests/assets/levels/test_1.lev").unwrap();
+    let level = Level::read_from(file).unwrap();
+    assert_eq!(level.path, None);
+
+    let mut buffer = vec![];
+    level.write_to(&mut buffer, Top10Save::No).unwrap();
+    let reloaded = Level::read_from(&buffer[..]).unwrap();
+    assert_eq!(reloaded.polygons, level.polygons);
+    assert_eq!(reloaded.objects, level.objects);
+    assert_eq!(reloaded.pictures, level.pictures);
+}
+
+#[test]
+fn truncated_level() {
+    let buffer = fs::read("tests/assets/levels/test_1.lev").unwrap();
+    assert_eq!(
+        Level::from_bytes(&buffer[..100]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: 94,
+            section: "header",
+        }
+    );
+    assert_eq!(
+        Level::from_bytes(&buffer[..buffer.len() - 2]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: buffer.len() - 4,
+            section: "end of file",
+        }
+    );
+}
+
+#[test]
+fn hostile_level_counts() {
+    let mut level = Level::new();
+    level.objects.clear();
+    let mut buffer = level.to_bytes(Top10Save::No).unwrap();
+    // Object count sits right after the single default polygon.
+    let offset = 130 + 8 + 8 + 4 * 16;
+    buffer[offset..offset + 8].copy_from_slice(&1e300f64.to_bits().to_le_bytes());
+    assert_eq!(
+        Level::from_bytes(&buffer).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: offset + 8,
+            section: "objects",
+        }
+    );
+    assert!(LevelHeader::from_bytes(&buffer).is_err());
+}
+
+#[test]
+fn damaged_level_does_not_panic() {
+    let original = fs::read("tests/assets/levels/test_1.lev").unwrap();
+    common::check_damaged(&original, 2000, |buffer| {
+        let _ = LevelHeader::from_bytes(buffer);
+        Level::from_bytes(buffer)
+    });
+}
+
+#[test]
+fn validate_level_fields() {
+    let mut level = Level::new();
+    assert_eq!(level.validate(), Ok(()));
+
+    // Names filling their whole field leave no room for the null terminator.
+    level.title = "a".repeat(50);
+    level.lgr = "a".repeat(15);
+    assert_eq!(level.validate(), Ok(()));
+    assert!(level.to_bytes(Top10Save::No).is_ok());
+    level.title = "a".repeat(51);
+    level.sky = "sköy".into();
+    level.objects.push(Object {
+        position: Position::new(1., 1.),
+        object_type: ObjectType::Apple {
+            gravity: GravityDirection::None,
+            animation: 10,
+        },
+    });
+    level.pictures.push(Picture {
+        kind: PictureKind::Texture {
+            texture: "stone1".into(),
+            mask: "stonemask1".into(),
+        },
+        distance: -1,
+        ..Picture::new()
+    });
+
+    let errors = level.validate().unwrap_err();
+    let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        [
+            "title",
+            "sky",
+            "objects[2].animation",
+            "pictures[0].mask",
+            "pictures[0].distance",
+        ]
+    );
+    assert_eq!(
+        errors[0].kind,
+        FieldErrorKind::TooLong {
+            length: 51,
+            max: 50,
+        }
+    );
+    assert_eq!(errors[1].kind, FieldErrorKind::NonASCII);
+    assert_eq!(
+        errors[2].kind,
+        FieldErrorKind::OutOfRange {
+            value: 10,
+            min: 1,
+            max: 9,
+        }
+    );
+    assert_eq!(
+        errors[3].kind,
+        FieldErrorKind::TooLong { length: 10, max: 9 }
+    );
+    assert!(level.to_bytes(Top10Save::No).is_err());
+}
@@ -1,7 +1,12 @@
 extern crate elma;
+extern crate rand;
+
+mod common;
 
 use elma::lgr::*;
+use elma::{Clip, ElmaError};
 use std::env;
+use std::fs;
 
 #[test]
 fn correctly_loads_lgr_1() {
@@ -30,3 +35,56 @@ fn correctly_loads_saves_and_reloads_lgrs() {
     let reloaded_across_lgr = LGR::load(&dir).unwrap();
     assert_eq!(orig_across_lgr, reloaded_across_lgr);
 }
+
+#[test]
+fn read_from_and_write_to_lgr() {
+    let file = fs::File::open("tests/assets/lgr/Across.lgr").unwrap();
+    let lgr = LGR::read_from(file).unwrap();
+    assert_eq!(lgr.path, None);
+
+    let mut buffer = vec![];
+    lgr.write_to(&mut buffer).unwrap();
+    assert_eq!(LGR::read_from(&buffer[..]).unwrap(), lgr);
+}
+
+fn small_lgr() -> Vec<u8> {
+    let mut lgr = LGR::new();
+    lgr.picture_list.push(Picture {
+        name: "qgrass".to_string(),
+        picture_type: PictureType::Texture,
+        distance: 450,
+        clipping: Clip::Ground,
+        transparency: Transparency::Solid,
+    });
+    for name in &["qgrass.pcx", "q1body.pcx"] {
+        lgr.picture_data.push(PictureData {
+            name: name.to_string(),
+            data: vec![10, 5, 1, 8, 0, 0, 0, 0, 7, 0, 3, 0],
+        });
+    }
+    lgr.to_bytes().unwrap()
+}
+
+#[test]
+fn truncated_lgr_does_not_panic() {
+    let buffer = small_lgr();
+    assert_eq!(
+        LGR::from_bytes(&buffer[..3]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: 0,
+            section: "header",
+        }
+    );
+    assert_eq!(
+        LGR::from_bytes(&buffer[..30]).unwrap_err(),
+        ElmaError::UnexpectedEof {
+            offset: 17,
+            section: "picture list",
+        }
+    );
+}
+
+#[test]
+fn damaged_lgr_does_not_panic() {
+    common::check_damaged(&small_lgr(), 2000, |buffer| LGR::from_bytes(buffer));
+}
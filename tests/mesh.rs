@@ -0,0 +1,195 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use common::polygon;
+use elma::geometry::{area, contains, union};
+use elma::lev::*;
+use elma::mesh::*;
+use elma::Position;
+use rand::Rng;
+
+fn square(x: f64, y: f64, size: f64) -> Polygon {
+    polygon(
+        false,
+        &[(x, y), (x + size, y), (x + size, y + size), (x, y + size)],
+    )
+}
+
+// Every triangle is counter-clockwise and inside the region, and together they cover it.
+fn check_mesh(mesh: &Mesh, polygons: &[Polygon]) {
+    let expected: f64 = union(polygons, &[]).iter().map(|p| area(&p.vertices)).sum();
+    assert!(
+        (mesh.area() - expected).abs() < 1e-6 * expected.max(1.),
+        "{} != {}",
+        mesh.area(),
+        expected
+    );
+    assert_eq!(mesh.indices.len() % 3, 0);
+    for triangle in mesh.indices.chunks(3) {
+        let corners: Vec<_> = triangle
+            .iter()
+            .map(|&i| mesh.vertices[i as usize])
+            .collect();
+        assert!(area(&corners) > 0.);
+        let centroid = Position::new(
+            (corners[0].x + corners[1].x + corners[2].x) / 3.,
+            (corners[0].y + corners[1].y + corners[2].y) / 3.,
+        );
+        assert!(contains(polygons, &centroid), "{:?}", corners);
+    }
+}
+
+#[test]
+fn square_with_holes() {
+    let polygons = vec![
+        square(0., 0., 20.),
+        square(2., 2., 4.),
+        square(10., 2., 4.),
+        // Island inside a hole.
+        square(11., 3., 2.),
+        // Hole touching the outer boundary at a corner.
+        polygon(false, &[(20., 20.), (16., 18.), (18., 16.)]),
+    ];
+    let mesh = triangulate(&polygons);
+    check_mesh(&mesh, &polygons);
+    assert!((mesh.area() - (400. - 16. - 16. + 4. - 6.)).abs() < 1e-9);
+}
+
+#[test]
+fn overlapping_and_self_intersecting() {
+    let polygons = vec![
+        square(0., 0., 10.),
+        square(5., 5., 10.),
+        polygon(false, &[(20., 0.), (30., 10.), (30., 0.), (20., 10.)]),
+    ];
+    let mesh = triangulate(&polygons);
+    check_mesh(&mesh, &polygons);
+    assert!((mesh.area() - (100. + 100. - 2. * 25. + 50.)).abs() < 1e-9);
+}
+
+#[test]
+fn comb_with_holes_between_teeth() {
+    // Holes whose bridges have to pass between the teeth of a comb.
+    let mut outer = vec![(0., 0.), (40., 0.), (40., 20.)];
+    for tooth in (0..4).rev() {
+        let x = tooth as f64 * 10.;
+        outer.push((x + 8., 20.));
+        outer.push((x + 8., 4.));
+        outer.push((x + 7., 4.));
+        outer.push((x + 7., 20.));
+    }
+    outer.push((0., 20.));
+    let mut polygons = vec![polygon(false, &outer)];
+    for tooth in 0..4 {
+        let x = tooth as f64 * 10.;
+        polygons.push(polygon(
+            false,
+            &[(x + 2., 10.), (x + 5., 10.), (x + 5., 15.), (x + 2., 15.)],
+        ));
+    }
+    let mesh = triangulate(&polygons);
+    check_mesh(&mesh, &polygons);
+}
+
+#[test]
+fn random_levels() {
+    let mut rng = common::rng();
+    for _ in 0..40 {
+        let mut polygons = vec![];
+        for _ in 0..rng.gen_range(1..6) {
+            let (cx, cy) = (rng.gen_range(-30. ..30.), rng.gen_range(-30. ..30.));
+            let count = rng.gen_range(3..20);
+            let mut vertices = vec![];
+            for n in 0..count {
+                let angle = n as f64 / count as f64 * 2. * ::std::f64::consts::PI;
+                let radius = rng.gen_range(2. ..25.);
+                vertices.push((cx + radius * angle.cos(), cy + radius * angle.sin()));
+            }
+            if rng.gen() {
+                vertices.reverse();
+            }
+            polygons.push(polygon(false, &vertices));
+        }
+        check_mesh(&triangulate(&polygons), &polygons);
+    }
+
+    // Many holes in one polygon.
+    for _ in 0..20 {
+        let mut polygons = vec![square(0., 0., 100.)];
+        for _ in 0..rng.gen_range(1..30) {
+            let (x, y) = (rng.gen_range(1. ..90.), rng.gen_range(1. ..90.));
+            polygons.push(square(x, y, rng.gen_range(0.5..9.)));
+        }
+        check_mesh(&triangulate(&polygons), &polygons);
+    }
+}
+
+#[test]
+fn level_meshes_separate_grass() {
+    let mut level = Level::new();
+    level.polygons.push(polygon(
+        true,
+        &[(0., 0.), (5., 1.), (10., 0.), (10., -1.), (0., -1.)],
+    ));
+    // Clockwise grass polygon.
+    level
+        .polygons
+        .push(polygon(true, &[(0., 0.), (0., 1.), (1., 1.)]));
+    let meshes = triangulate_level(&level, 2.);
+    assert_eq!(meshes.air.triangle_count(), 2);
+    assert!((meshes.air.area() - 70.).abs() < 1e-9);
+    assert!((meshes.ground.area() - (14. * 11. - 70.)).abs() < 1e-9);
+    assert_eq!(meshes.grass.triangle_count(), 4);
+    assert!((meshes.grass.area() - (10. + 5. + 0.5)).abs() < 1e-9);
+    assert_eq!(meshes.grass.vertices.len(), 8);
+}
+
+#[test]
+fn ground_surrounds_air() {
+    let mut level = Level::new();
+    level.polygons = vec![square(0., 0., 20.), square(5., 5., 4.)];
+    let meshes = triangulate_level(&level, 1.);
+    // The island in the air is ground as well.
+    assert!((meshes.air.area() - (400. - 16.)).abs() < 1e-9);
+    assert!((meshes.ground.area() - (22. * 22. - 400. + 16.)).abs() < 1e-9);
+    for triangle in meshes.ground.indices.chunks(3) {
+        let corners: Vec<_> = triangle
+            .iter()
+            .map(|&i| meshes.ground.vertices[i as usize])
+            .collect();
+        let centroid = Position::new(
+            (corners[0].x + corners[1].x + corners[2].x) / 3.,
+            (corners[0].y + corners[1].y + corners[2].y) / 3.,
+        );
+        assert!(!contains(&level.polygons, &centroid), "{:?}", corners);
+    }
+
+    level.polygons.clear();
+    assert_eq!(triangulate_level(&level, 1.), LevelMeshes::default());
+}
+
+#[test]
+fn unbounded_vertices_do_not_panic() {
+    // Damaged level files can hold any coordinates.
+    let mut level = Level::new();
+    level.polygons = vec![
+        square(0., 0., 20.),
+        square(5., 5., 4.),
+        square(12., 5., 4.),
+        polygon(false, &[(1., 1.), (f64::NAN, 2.), (3., f64::NAN)]),
+        polygon(
+            false,
+            &[
+                (-4.67, -9.7),
+                (-1e308, -7.46),
+                (8.9, -6.),
+                (f64::NEG_INFINITY, -4.21),
+            ],
+        ),
+    ];
+    triangulate_level(&level, 1.);
+    level.polygons[1].vertices[0].x = f64::NAN;
+    triangulate_level(&level, 1.);
+}
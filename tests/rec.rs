@@ -1,4 +1,7 @@
 extern crate elma;
+extern crate rand;
+
+mod common;
 
 use elma::rec::*;
 use elma::Position;
@@ -392,3 +395,498 @@ fn replay_get_time_hs_unfinished_event_single_2_frame_diff() {
     assert_eq!(time, 856);
     assert_eq!(finished, false);
 }
+
+#[test]
+fn read_from_and_write_to_replay() {
+    let file = fs::File::open(PATH_TEST_2).unwrap();
+    let replay = Replay::read_from(file).unwrap();
+    assert_eq!(replay.path, None);
+
+    let mut buffer = vec![];
+    replay.write_to(&mut buffer).unwrap();
+    assert_eq!(buffer, fs::read(PATH_TEST_2).unwrap());
+    assert_eq!(Replay::read_from(&buffer[..]).unwrap(), replay);
+}
+
+#[test]
+fn damaged_replay_does_not_panic() {
+    let original = fs::read(PATH_TEST_3).unwrap();
+    common::check_damaged(&original, 2000, |buffer| Replay::from_bytes(buffer));
+    // Cut inside the events of the first ride.
+    assert_eq!(
+        Replay::from_bytes(&original[..original.len() / 2]),
+        Err(elma::ElmaError::InvalidReplayFile)
+    );
+    // A negative frame count must not be used as an allocation size.
+    let mut buffer = original.clone();
+    buffer[..4].copy_from_slice(&(-1i32).to_le_bytes());
+    assert_eq!(
+        Replay::from_bytes(&buffer),
+        Err(elma::ElmaError::InvalidReplayFile)
+    );
+}
+
+#[test]
+fn relink_replays_to_new_level_link() {
+    let mut level = elma::lev::Level::new();
+    level.path = Some("levels/MyLevel.lev".into());
+    let (old_link, new_link) = (1234, 5678);
+    level.link = new_link;
+
+    let replay = |name: &str, link: u32| {
+        let mut replay = Replay::new();
+        replay.level = name.into();
+        replay.link = link;
+        replay
+    };
+    let mut replays = vec![
+        replay("mylevel.lev", old_link),
+        replay("MYLEVEL.LEV", new_link),
+        replay("other.lev", old_link),
+        replay("MyLevel.lev", 9),
+        replay("MyLevel.lev", old_link),
+    ];
+    assert_eq!(
+        relink_replays(&level, old_link, &mut replays),
+        vec![
+            RelinkStatus::Updated,
+            RelinkStatus::AlreadyLinked,
+            RelinkStatus::OtherLevel("other.lev".into()),
+            RelinkStatus::OtherLink(9),
+            RelinkStatus::Updated,
+        ]
+    );
+    let links: Vec<u32> = replays.iter().map(|r| r.link).collect();
+    assert_eq!(links, vec![new_link, new_link, old_link, 9, new_link]);
+
+    // Without a path, only the link is compared.
+    level.path = None;
+    replays[2].link = old_link;
+    let status = relink_replays(&level, old_link, &mut replays[2..3]);
+    assert_eq!(status, vec![RelinkStatus::Updated]);
+}
+
+#[test]
+fn ride_state_at_interpolates_frames() {
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    let ride = &replay.rides[0];
+    for (n, frame) in ride.frames.iter().enumerate().step_by(37) {
+        let state = ride.state_at(n as f64 * 33.333).unwrap();
+        let expected = BikeState::from(frame);
+        assert!((state.bike.x - expected.bike.x).abs() < 1e-6);
+        assert!((state.head.y - expected.head.y).abs() < 1e-6);
+        assert_eq!(state.direction, expected.direction);
+    }
+    assert_eq!(ride.state_at(-100.), Some(BikeState::from(&ride.frames[0])));
+    assert_eq!(
+        ride.state_at(1e9),
+        Some(BikeState::from(ride.frames.last().unwrap()))
+    );
+    assert_eq!(Ride::new().state_at(0.), None);
+}
+
+#[test]
+fn ride_state_at_wraps_angles() {
+    let mut ride = Ride::new();
+    ride.frames.push(Frame {
+        bike: Position::new(1., 2.),
+        left_wheel: Position::new(-850, -600),
+        head: Position::new(0, 500),
+        rotation: 9900,
+        left_wheel_rotation: 240,
+        right_wheel_rotation: 100,
+        throttle_and_dir: 0b11,
+        ..Frame::new()
+    });
+    ride.frames.push(Frame {
+        bike: Position::new(2., 4.),
+        left_wheel: Position::new(-850, -400),
+        head: Position::new(0, 500),
+        rotation: 100,
+        left_wheel_rotation: 10,
+        right_wheel_rotation: 50,
+        ..Frame::new()
+    });
+    let turn = 2. * std::f64::consts::PI;
+    let state = ride.state_at(33.333 / 4.).unwrap();
+    assert!((state.bike.x - 1.25).abs() < 1e-9);
+    assert!((state.bike.y - 2.5).abs() < 1e-9);
+    assert!((state.left_wheel.x - (1.25 - 0.85)).abs() < 1e-9);
+    assert!((state.left_wheel.y - (2.5 - 0.55)).abs() < 1e-9);
+    assert!((state.head.y - 3.).abs() < 1e-9);
+    // 9900 to 100 goes forward through 0, not back through 5000.
+    assert!((state.rotation - 9950. / 10000. * turn).abs() < 1e-9);
+    assert!((state.left_wheel_rotation - 245. / 250. * turn).abs() < 1e-9);
+    assert!((state.right_wheel_rotation - 87.5 / 250. * turn).abs() < 1e-9);
+    assert!(state.throttle);
+    assert_eq!(state.direction, Direction::Right);
+
+    let state = ride.state_at(33.333 * 0.75).unwrap();
+    assert!((state.rotation - 50. / 10000. * turn).abs() < 1e-9);
+    assert!((state.left_wheel_rotation - 5. / 250. * turn).abs() < 1e-9);
+}
+
+#[test]
+fn frame_accessors_decode_fields() {
+    let frame = Frame {
+        bike: Position::new(2.5, -1.25),
+        left_wheel: Position::new(-850, -600),
+        right_wheel: Position::new(849, -601),
+        head: Position::new(-1, 439),
+        rotation: 2500,
+        left_wheel_rotation: 250,
+        right_wheel_rotation: 125,
+        throttle_and_dir: 0b1111_1110,
+        back_wheel_rot_speed: 51,
+        collision_strength: 255,
+    };
+    assert_eq!(frame.bike_position(), Position::new(2.5, -1.25));
+    let close =
+        |a: Position<f64>, b: (f64, f64)| (a.x - b.0).abs() < 1e-9 && (a.y - b.1).abs() < 1e-9;
+    assert!(close(frame.left_wheel_position(), (1.65, -1.85)));
+    assert!(close(frame.right_wheel_position(), (3.349, -1.851)));
+    assert!(close(frame.head_position(), (2.499, -0.811)));
+    let pi = std::f64::consts::PI;
+    assert!((frame.rotation_angle() - pi / 2.).abs() < 1e-9);
+    assert_eq!(frame.left_wheel_angle(), 0.);
+    assert!((frame.right_wheel_angle() - pi).abs() < 1e-9);
+    assert!(!frame.throttle());
+    assert_eq!(frame.direction(), Direction::Right);
+    assert!((frame.back_wheel_speed() - 0.2).abs() < 1e-9);
+    assert_eq!(frame.collision_volume(), 1.);
+
+    // Recorded frames agree with the decoded bike state.
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    for frame in &replay.rides[0].frames {
+        let state = BikeState::from(frame);
+        assert_eq!(state.head, frame.head_position());
+        assert!(frame.rotation_angle() >= 0. && frame.rotation_angle() < 2. * pi);
+        assert!(frame.left_wheel_angle() >= 0. && frame.left_wheel_angle() < 2. * pi);
+    }
+}
+
+#[test]
+fn ride_motion_series() {
+    let dt = 33.333 / 1000.;
+    // Moving at 3 units per second to the upper right while rotating backwards through 0.
+    let mut ride = Ride::new();
+    for n in 0..60_i32 {
+        let t = f64::from(n) * dt;
+        ride.frames.push(Frame {
+            bike: Position::new((1.8 * t) as f32, (2.4 * t) as f32),
+            rotation: ((1000 - 100 * n).rem_euclid(10000)) as i16,
+            ..Frame::new()
+        });
+    }
+    let turn = 2. * std::f64::consts::PI;
+    for smoothing in &[0, 3] {
+        let motion = ride.motion(*smoothing);
+        assert_eq!(motion.len(), 60);
+        for m in &motion {
+            assert!((m.velocity.x - 1.8).abs() < 1e-3, "{:?}", m);
+            assert!((m.velocity.y - 2.4).abs() < 1e-3);
+            assert!((m.speed - 3.).abs() < 1e-3);
+            assert!(m.acceleration.abs() < 0.1);
+            assert!((m.angular_velocity + 0.01 * turn / dt).abs() < 1e-6);
+        }
+        assert!((motion[30].time - 30. * 33.333).abs() < 1e-9);
+    }
+    assert!((ride.top_speed() - 3.).abs() < 1e-3);
+    assert!((ride.average_speed() - 3.).abs() < 1e-3);
+    assert!((ride.distance() - 3. * 59. * dt).abs() < 1e-3);
+
+    // Speeding up by 2 units per second squared.
+    let mut ride = Ride::new();
+    for n in 0..60 {
+        let t = n as f64 * dt;
+        ride.frames.push(Frame {
+            bike: Position::new((t * t) as f32, 0.),
+            ..Frame::new()
+        });
+    }
+    let motion = ride.motion(0);
+    for m in &motion[2..58] {
+        assert!((m.speed - 2. * m.time / 1000.).abs() < 1e-2);
+        assert!((m.acceleration - 2.).abs() < 0.1);
+    }
+    let smoothed = ride.motion(5);
+    assert!((smoothed[30].acceleration - 2.).abs() < 1e-2);
+
+    let empty = Ride::new();
+    assert!(empty.motion(2).is_empty());
+    assert_eq!(empty.top_speed(), 0.);
+    assert_eq!(empty.average_speed(), 0.);
+
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    let ride = &replay.rides[0];
+    assert!(ride.distance() > 0.);
+    assert!(ride.top_speed() > ride.average_speed());
+    let smoothed_top = ride.motion(3).iter().map(|m| m.speed).fold(0., f64::max);
+    assert!(smoothed_top <= ride.top_speed());
+}
+
+#[test]
+fn validate_replay_against_level() {
+    use elma::lev::{GravityDirection, Level, Object, ObjectType};
+
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    let object = |object_type| Object {
+        position: Position::new(5., 1.),
+        object_type,
+    };
+    let apple = ObjectType::Apple {
+        gravity: GravityDirection::None,
+        animation: 1,
+    };
+    // The replay takes apples 1 and 2 and then touches flower 3, so the level has a killer at 0.
+    let level = |killers: usize, apples: usize| {
+        let mut level = Level::new();
+        level.link = replay.link;
+        level.path = Some("levels/TUTOR14.LEV".into());
+        for _ in 0..apples {
+            level.objects.push(object(apple.clone()));
+        }
+        for _ in 0..killers {
+            level.objects.push(object(ObjectType::Killer));
+        }
+        level
+    };
+    assert_eq!(replay.validate_against(&level(1, 2)), Ok(()));
+
+    // With one apple, index 2 is the flower, but two apples are taken.
+    assert_eq!(
+        replay.validate_against(&level(1, 1)),
+        Err(vec![ReplayMismatch::TooManyApples {
+            taken: 2,
+            apples: 1,
+        }])
+    );
+    // Without the killer, index 2 is the flower, touched while an apple is left.
+    assert_eq!(
+        replay.validate_against(&level(0, 2)),
+        Err(vec![ReplayMismatch::NotFinished])
+    );
+
+    let mut other = level(1, 2);
+    other.link = replay.link.wrapping_add(1);
+    other.path = Some("tutor15.lev".into());
+    assert_eq!(
+        replay.validate_against(&other),
+        Err(vec![
+            ReplayMismatch::Link {
+                replay: replay.link,
+                level: other.link,
+            },
+            ReplayMismatch::LevelName {
+                replay: "tutor14.lev".into(),
+                level: "tutor15.lev".into(),
+            },
+        ])
+    );
+
+    let mut small = level(0, 0);
+    small.path = None;
+    let errors = replay.validate_against(&small).unwrap_err();
+    let indices: Vec<i16> = errors
+        .iter()
+        .filter_map(|e| match *e {
+            ReplayMismatch::ObjectIndex { ride: 0, index, .. } => Some(index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(indices, vec![2, 3]);
+    assert_eq!(
+        errors[2..],
+        [
+            ReplayMismatch::TooManyApples {
+                taken: 2,
+                apples: 0,
+            },
+            ReplayMismatch::NotFinished,
+        ]
+    );
+}
+
+#[test]
+fn replay_outcome_on_level() {
+    use elma::lev::{Level, Object, ObjectType};
+    use elma::Time;
+
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    let ride = &replay.rides[0];
+    let level = |types: &[ObjectType]| {
+        let mut level = Level::new();
+        for object_type in types {
+            level.objects.push(Object {
+                object_type: object_type.clone(),
+                ..Object::new()
+            });
+        }
+        level
+    };
+    let apple = ObjectType::default();
+    let hundredths = |ms: f64| Time(ms.round() as i32 / 10);
+
+    // Apples 1 and 2, then the flower at 3.
+    let outcome = replay.outcome(&level(&[ObjectType::Killer, apple.clone(), apple.clone()]));
+    assert_eq!(
+        outcome,
+        Outcome::Finished(Time(replay.get_time_hs().0 as i32))
+    );
+    assert_eq!(outcome.time(), Time(1464));
+
+    // The first touch is a killer.
+    let first_touch = ride
+        .events
+        .iter()
+        .find(|e| e.event_type == EventType::ObjectTouch(1))
+        .unwrap();
+    let killers = vec![ObjectType::Killer; 3];
+    assert_eq!(
+        replay.outcome(&level(&killers)),
+        Outcome::Killed(hundredths(first_touch.time * 2_289.377_289_38))
+    );
+
+    // Times come straight from the file; a NaN one sorts after the others.
+    let mut broken = Replay::load(PATH_TEST_1).unwrap();
+    broken.rides[0].events.insert(
+        0,
+        Event {
+            time: f64::NAN,
+            event_type: EventType::ObjectTouch(0),
+        },
+    );
+    assert_eq!(
+        broken.outcome(&level(&killers)),
+        replay.outcome(&level(&killers))
+    );
+    assert!(broken.validate_against(&level(&killers)).is_err());
+
+    // The flower at 3 is touched with an apple left.
+    let outcome = replay.outcome(&level(&[apple.clone(), apple.clone(), apple.clone()]));
+    assert_eq!(outcome, Outcome::Stopped(hundredths(ride.get_frame_time())));
+    assert!(outcome.time() > Time(1464));
+
+    assert_eq!(
+        Replay::new().outcome(&Level::new()),
+        Outcome::Stopped(Time(0))
+    );
+}
+
+#[test]
+fn ride_splits_and_comparison() {
+    let replay = Replay::load(PATH_TEST_1).unwrap();
+    let splits = replay.rides[0].splits();
+    let objects: Vec<_> = splits.iter().map(|s| s.object).collect();
+    assert_eq!(objects, vec![Some(1), Some(2)]);
+    assert!((splits[0].time - 3.5503 * 2_289.377_289_38).abs() < 0.5);
+    assert!(splits[0].time < splits[1].time);
+
+    let event = |time: f64, event_type| Event { time, event_type };
+    let mut other = Ride::new();
+    other.events = vec![
+        event(1., EventType::Turn),
+        event(1.5, EventType::ObjectTouch(2)),
+        event(1.5, EventType::Apple),
+        event(2., EventType::ObjectTouch(1)),
+        event(2., EventType::Apple),
+        // An apple without a touch before it.
+        event(3., EventType::Apple),
+        event(4., EventType::ObjectTouch(3)),
+    ];
+    let other_splits = other.splits();
+    assert_eq!(other_splits.len(), 3);
+    assert_eq!(other_splits[2].object, None);
+
+    let comparison = replay.rides[0].compare_splits(&other);
+    assert_eq!(comparison.deltas.len(), 2);
+    assert!((comparison.deltas[0] - (splits[0].time - 1.5 * 2_289.377_289_38)).abs() < 1e-9);
+    assert!((comparison.deltas[1] - (splits[1].time - 2. * 2_289.377_289_38)).abs() < 1e-9);
+    assert_eq!(comparison.reordered, vec![0, 1]);
+
+    let same = replay.compare_splits(&replay);
+    assert_eq!(same.deltas, vec![0., 0.]);
+    assert!(same.reordered.is_empty());
+    assert_eq!(
+        replay.compare_splits(&Replay::new()),
+        SplitComparison::default()
+    );
+}
+
+#[test]
+fn trim_ride_frames_and_events() {
+    let scale = 33.333 / 2_289.377_289_38;
+    let mut ride = Ride::new();
+    for n in 0..10 {
+        ride.frames.push(Frame {
+            bike: Position::new(n as f32, 0.),
+            ..Frame::new()
+        });
+    }
+    let event = |frame: f64, event_type| Event {
+        time: frame * scale,
+        event_type,
+    };
+    ride.events = vec![
+        event(1., EventType::Turn),
+        event(3., EventType::VoltLeft),
+        event(6.5, EventType::VoltRight),
+        event(7., EventType::ObjectTouch(0)),
+    ];
+    ride.trim_frames(3, 7);
+    let xs: Vec<f32> = ride.frames.iter().map(|f| f.bike.x).collect();
+    assert_eq!(xs, vec![3., 4., 5., 6.]);
+    let events: Vec<_> = ride
+        .events
+        .iter()
+        .map(|e| ((e.time / scale * 1e6).round() / 1e6, &e.event_type))
+        .collect();
+    assert_eq!(
+        events,
+        vec![(0., &EventType::VoltLeft), (3.5, &EventType::VoltRight)]
+    );
+
+    // Windows past the end of the ride.
+    ride.trim_frames(2, 100);
+    assert_eq!(ride.frames.len(), 2);
+    assert_eq!(ride.events.len(), 1);
+    ride.trim_frames(5, 1);
+    assert!(ride.frames.is_empty() && ride.events.is_empty());
+}
+
+#[test]
+fn trim_replay_keeps_timelines_consistent() {
+    let original = Replay::load(PATH_TEST_1).unwrap();
+    let mut replay = Replay::load(PATH_TEST_1).unwrap();
+    replay.trim(7000., 12000.);
+    let (ride, full) = (&replay.rides[0], &original.rides[0]);
+    // Frames 211 to 360.
+    assert_eq!(ride.frames.len(), 150);
+    assert_eq!(ride.frames[0], full.frames[211]);
+    let offset = 211. * 33.333;
+    for time in &[0., 500., 1234.5, 3900.] {
+        let a = ride.state_at(*time).unwrap();
+        let b = full.state_at(time + offset).unwrap();
+        assert!((a.bike.x - b.bike.x).abs() < 1e-9 && (a.rotation - b.rotation).abs() < 1e-9);
+    }
+    // Both apples are taken in the window.
+    let splits = ride.splits();
+    assert_eq!(splits.len(), 2);
+    for (split, full_split) in splits.iter().zip(full.splits()) {
+        assert!((split.time - (full_split.time - offset)).abs() < 1e-6);
+        assert_eq!(split.object, full_split.object);
+    }
+    assert!(ride
+        .events
+        .iter()
+        .all(|e| e.time >= 0. && e.time * 2_289.377_289_38 < ride.get_frame_time()));
+
+    // Trimmed multi-player replays are written and read back.
+    let mut multi = Replay::load(PATH_TEST_2).unwrap();
+    assert!(multi.is_multi());
+    multi.trim(100., 2000.);
+    let lengths: Vec<usize> = multi.rides.iter().map(|r| r.frames.len()).collect();
+    assert!(lengths.iter().all(|&n| n > 0 && n <= 57));
+    let bytes = multi.to_bytes().unwrap();
+    assert_eq!(Replay::from_bytes(&bytes).unwrap().rides, multi.rides);
+}
@@ -0,0 +1,166 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use common::polygon;
+use elma::geometry::area;
+use elma::lev::*;
+use elma::repair::*;
+
+fn level(polygons: Vec<Polygon>) -> Level {
+    let mut level = Level::new();
+    level.polygons = polygons;
+    level
+}
+
+#[test]
+fn removes_degenerate_vertices() {
+    let mut level = level(vec![polygon(
+        false,
+        &[
+            (0., 0.),
+            (0., 0.),
+            (5., 0.),
+            (10., 0.),
+            (10., 5.),
+            (10., 10.),
+            (5., 10.),
+            // Spike sticking out of the left edge.
+            (5., 12.),
+            (5., 10.),
+            (0., 10.),
+            (0., 0.0000001),
+        ],
+    )]);
+    let repairs = repair_polygons(&mut level, &RepairOptions::new());
+    assert_eq!(
+        repairs,
+        vec![
+            Repair::RemovedDuplicateVertex {
+                polygon: 0,
+                vertex: 1
+            },
+            Repair::RemovedDuplicateVertex {
+                polygon: 0,
+                vertex: 10
+            },
+            Repair::RemovedCollinearVertex {
+                polygon: 0,
+                vertex: 2
+            },
+            Repair::RemovedCollinearVertex {
+                polygon: 0,
+                vertex: 4
+            },
+            Repair::RemovedCollinearVertex {
+                polygon: 0,
+                vertex: 7
+            },
+            Repair::RemovedCollinearVertex {
+                polygon: 0,
+                vertex: 8
+            },
+            Repair::RemovedCollinearVertex {
+                polygon: 0,
+                vertex: 6
+            },
+        ]
+    );
+    assert_eq!(
+        level.polygons,
+        vec![polygon(
+            false,
+            &[(0., 0.), (10., 0.), (10., 10.), (0., 10.)]
+        )]
+    );
+}
+
+#[test]
+fn grass_keeps_collinear_vertices() {
+    let grass = polygon(
+        true,
+        &[(0., 0.), (5., 0.), (10., 0.), (10., -1.), (0., -1.)],
+    );
+    let mut level = level(vec![grass.clone()]);
+    assert_eq!(repair_polygons(&mut level, &RepairOptions::new()), vec![]);
+    assert_eq!(level.polygons, vec![grass]);
+}
+
+#[test]
+fn removes_invalid_and_duplicate_polygons() {
+    let square = polygon(false, &[(0., 0.), (10., 0.), (10., 10.), (0., 10.)]);
+    let mut reversed = square.clone();
+    reversed.vertices.reverse();
+    reversed.vertices.rotate_left(1);
+    let mut level = level(vec![
+        polygon(false, &[(20., 0.), (21., 0.)]),
+        square.clone(),
+        polygon(false, &[(20., 0.), (21., 0.), (22., 0.)]),
+        polygon(true, &[(0., 0.), (0., 0.), (1., 1.)]),
+        reversed,
+        square.clone(),
+    ]);
+    let repairs = repair_polygons(&mut level, &RepairOptions::new());
+    assert_eq!(
+        repairs,
+        vec![
+            Repair::RemovedPolygon { polygon: 0 },
+            Repair::RemovedCollinearVertex {
+                polygon: 2,
+                vertex: 0
+            },
+            Repair::RemovedPolygon { polygon: 2 },
+            Repair::RemovedDuplicateVertex {
+                polygon: 3,
+                vertex: 1
+            },
+            Repair::RemovedPolygon { polygon: 3 },
+            Repair::RemovedDuplicatePolygons {
+                first: 1,
+                second: 4
+            },
+        ]
+    );
+    assert_eq!(level.polygons, vec![square]);
+}
+
+#[test]
+fn splits_self_intersections() {
+    let bowtie = polygon(false, &[(0., 0.), (10., 10.), (10., 0.), (0., 10.)]);
+    let square = polygon(false, &[(20., 0.), (30., 0.), (30., 10.), (20., 10.)]);
+    assert!(self_intersects(&bowtie));
+    assert!(!self_intersects(&square));
+    // Touching itself at a vertex.
+    assert!(self_intersects(&polygon(
+        false,
+        &[(0., 0.), (4., 0.), (2., 2.), (4., 4.), (0., 4.), (2., 2.)]
+    )));
+
+    let mut original = level(vec![bowtie.clone(), square.clone()]);
+    let mut unchanged = level(vec![bowtie, square.clone()]);
+    assert_eq!(
+        repair_polygons(&mut unchanged, &RepairOptions::new()),
+        vec![]
+    );
+    assert_eq!(unchanged.polygons, original.polygons);
+
+    let options = RepairOptions {
+        split_self_intersections: true,
+        ..RepairOptions::new()
+    };
+    let repairs = repair_polygons(&mut original, &options);
+    assert_eq!(
+        repairs,
+        vec![Repair::SplitPolygon {
+            polygon: 0,
+            parts: 2
+        }]
+    );
+    assert_eq!(original.polygons.len(), 3);
+    for part in &original.polygons[..2] {
+        assert!(!self_intersects(part));
+        assert!((area(&part.vertices).abs() - 25.).abs() < 1e-9);
+    }
+    assert_eq!(original.polygons[2], square);
+}
@@ -0,0 +1,196 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use common::polygon;
+use elma::geometry::area;
+use elma::lev::*;
+use elma::select::*;
+use elma::Position;
+
+fn object(x: f64, y: f64, object_type: ObjectType) -> Object {
+    Object {
+        position: Position::new(x, y),
+        object_type,
+    }
+}
+
+// Level::new() with a small room to the right, a grass strip crossing both and a picture.
+fn level() -> Level {
+    let mut level = Level::new();
+    level.polygons.push(polygon(
+        false,
+        &[(20., 0.), (24., 0.), (24., 4.), (20., 4.)],
+    ));
+    level
+        .polygons
+        .push(polygon(true, &[(5., 0.), (22., 0.), (22., -0.5)]));
+    level.objects.push(object(22., 1., ObjectType::Killer));
+    level.pictures.push(Picture {
+        position: Position::new(22., 3.),
+        ..Picture::new()
+    });
+    level
+}
+
+#[test]
+fn select_rect_and_lasso() {
+    let level = level();
+    let rect = Region::Rect(Position::new(25., 5.), Position::new(19., -1.));
+    assert_eq!(
+        select(&level, &rect),
+        Selection {
+            polygons: vec![1],
+            objects: vec![2],
+            pictures: vec![0],
+        }
+    );
+    assert!(select(
+        &level,
+        &Region::Rect(Position::new(50., 50.), Position::new(60., 60.))
+    )
+    .is_empty());
+
+    // A U-shaped lasso holds every corner of the room, but its notch cuts through the room.
+    let lasso = Region::Lasso(
+        [
+            (19., -1.),
+            (25., -1.),
+            (25., 5.),
+            (23., 5.),
+            (23., 2.),
+            (21., 2.),
+            (21., 5.),
+            (19., 5.),
+        ]
+        .iter()
+        .map(|&(x, y)| Position::new(x, y))
+        .collect(),
+    );
+    assert!(lasso.contains(&Position::new(22., 1.)));
+    assert!(!lasso.contains(&Position::new(22., 3.)));
+    assert_eq!(
+        select(&level, &lasso),
+        Selection {
+            polygons: vec![],
+            objects: vec![2],
+            pictures: vec![],
+        }
+    );
+}
+
+#[test]
+fn extract_with_clipping() {
+    let level = level();
+    let region = Region::Rect(Position::new(5., -1.), Position::new(22., 8.));
+
+    let fragment = extract(&level, &region, false);
+    assert_eq!(fragment.polygons, vec![level.polygons[2].clone()]);
+    assert_eq!(
+        fragment.objects,
+        vec![level.objects[1].clone(), level.objects[2].clone()]
+    );
+    assert_eq!(fragment.pictures, level.pictures);
+
+    let fragment = extract(&level, &region, true);
+    let areas: Vec<f64> = fragment
+        .polygons
+        .iter()
+        .map(|p| area(&p.vertices).abs())
+        .collect();
+    assert_eq!(areas, vec![35., 8., 4.25]);
+    let grass: Vec<bool> = fragment.polygons.iter().map(|p| p.grass).collect();
+    assert_eq!(grass, vec![false, false, true]);
+    for vertex in fragment.polygons.iter().flat_map(|p| &p.vertices) {
+        assert!(region.contains(vertex));
+    }
+}
+
+#[test]
+fn paste_into_level() {
+    let source = level();
+    let fragment = extract(
+        &source,
+        &Region::Rect(Position::new(-1., -1.), Position::new(30., 10.)),
+        false,
+    );
+    assert_eq!(fragment.objects.len(), 3);
+
+    let mut level = Level::new();
+    level.polygons.clear();
+    level.objects = vec![
+        object(-5., -5., ObjectType::Exit),
+        object(-3., -5., ObjectType::Player),
+    ];
+    let pasted = fragment
+        .paste(&mut level, Position::new(10., -20.))
+        .unwrap();
+    assert_eq!(
+        pasted,
+        Selection {
+            polygons: vec![0, 1, 2],
+            objects: vec![1, 2, 3],
+            pictures: vec![0],
+        }
+    );
+    assert!(level.check_topology().is_ok());
+    assert_eq!(level.polygons[1].vertices[2], Position::new(34., -16.));
+    assert_eq!(
+        level.objects,
+        vec![
+            object(-5., -5., ObjectType::Exit),
+            // The level's start moved to the pasted one.
+            object(12., 0.4 - 20., ObjectType::Player),
+            object(18., 0.4 - 20., ObjectType::Exit),
+            object(32., -19., ObjectType::Killer),
+        ]
+    );
+    assert_eq!(level.pictures[0].position, Position::new(32., -17.));
+    assert_eq!(level.pictures[0].kind, Picture::new().kind);
+
+    // Without a start of its own, the level takes the pasted one.
+    let mut level = Level::new();
+    level.objects.clear();
+    let pasted = fragment.paste(&mut level, Position::new(0., 0.)).unwrap();
+    assert_eq!(pasted.objects, vec![0, 1, 2]);
+    assert_eq!(level.objects, fragment.objects);
+}
+
+#[test]
+fn invalid_pastes() {
+    let mut fragment = Fragment::new();
+    fragment.objects = vec![
+        object(0., 0., ObjectType::Player),
+        object(1., 0., ObjectType::Player),
+    ];
+    let mut level = Level::new();
+    assert_eq!(
+        fragment.paste(&mut level, Position::new(0., 0.)),
+        Err(TopologyError::InvalidPlayerCount(2))
+    );
+    assert_eq!(level.objects.len(), 2);
+
+    let mut fragment = Fragment::new();
+    fragment.polygons = vec![Polygon::new(); 1000];
+    assert_eq!(
+        fragment.paste(&mut level, Position::new(0., 0.)),
+        Err(TopologyError::MaxPolygons(1))
+    );
+    fragment.polygons.pop();
+    fragment.objects = vec![object(0., 0., ObjectType::Killer); 251];
+    assert_eq!(
+        fragment.paste(&mut level, Position::new(0., 0.)),
+        Err(TopologyError::MaxObjects(1))
+    );
+    assert_eq!(level.polygons.len(), 1);
+    assert_eq!(level.objects.len(), 2);
+
+    // A start replaces the level's, so it does not count against the limit.
+    fragment.objects[0].object_type = ObjectType::Player;
+    fragment.objects.pop();
+    fragment.objects.push(object(0., 0., ObjectType::Exit));
+    assert!(fragment.paste(&mut level, Position::new(0., 0.)).is_ok());
+    assert_eq!(level.objects.len(), 252);
+    assert_eq!(level.polygons.len(), 1000);
+}
@@ -0,0 +1,228 @@
+extern crate elma;
+extern crate rand;
+
+mod common;
+
+use common::polygon;
+use elma::lev::*;
+use elma::spatial::*;
+use elma::Position;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+// A jagged ground polygon with a few boxes floating inside it.
+fn random_polygons(rng: &mut StdRng) -> Vec<Polygon> {
+    let mut outer = vec![];
+    for n in 0..60 {
+        let angle = n as f64 / 60. * 2. * ::std::f64::consts::PI;
+        let radius = rng.gen_range(40. ..80.);
+        outer.push((radius * angle.cos(), radius * angle.sin()));
+    }
+    let mut polygons = vec![polygon(false, &outer)];
+    for _ in 0..10 {
+        let (x, y) = (rng.gen_range(-25. ..25.), rng.gen_range(-25. ..25.));
+        let size = rng.gen_range(0.5..5.);
+        polygons.push(polygon(
+            false,
+            &[(x, y), (x + size, y), (x + size, y + size), (x, y + size)],
+        ));
+    }
+    polygons
+}
+
+fn brute_nearest(index: &SpatialIndex, point: Position<f64>) -> f64 {
+    index
+        .edges()
+        .iter()
+        .map(|edge| {
+            let (ex, ey) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+            let t = (((point.x - edge.from.x) * ex + (point.y - edge.from.y) * ey)
+                / (ex * ex + ey * ey))
+                .clamp(0., 1.);
+            (edge.from.x + ex * t - point.x).hypot(edge.from.y + ey * t - point.y)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn brute_raycast(
+    index: &SpatialIndex,
+    origin: Position<f64>,
+    direction: Position<f64>,
+) -> Option<f64> {
+    index
+        .edges()
+        .iter()
+        .filter_map(|edge| {
+            let (ex, ey) = (edge.to.x - edge.from.x, edge.to.y - edge.from.y);
+            let denominator = direction.x * ey - direction.y * ex;
+            let (ox, oy) = (edge.from.x - origin.x, edge.from.y - origin.y);
+            let t = (ox * ey - oy * ex) / denominator;
+            let s = (ox * direction.y - oy * direction.x) / denominator;
+            if t >= 0. && (0. ..=1.).contains(&s) {
+                Some(t)
+            } else {
+                None
+            }
+        })
+        .fold(None, |best: Option<f64>, t| {
+            Some(best.map_or(t, |b| b.min(t)))
+        })
+}
+
+#[test]
+fn edges_skip_grass() {
+    let mut level = Level::new();
+    level.polygons.push(Polygon {
+        grass: true,
+        vertices: vec![
+            Position::new(1., 1.),
+            Position::new(2., 1.),
+            Position::new(2., 2.),
+        ],
+    });
+    let index = SpatialIndex::new(&level);
+    assert_eq!(index.edges().len(), 4);
+    assert!(index.edges().iter().all(|e| e.polygon == 0));
+    assert_eq!(index.edges()[1].vertex, 1);
+    assert_eq!(index.edges()[3].to, level.polygons[0].vertices[0]);
+
+    let empty = SpatialIndex::from_polygons(&[]);
+    assert_eq!(empty.nearest_edge(Position::new(0., 0.)), None);
+    assert_eq!(
+        empty.raycast(Position::new(0., 0.), Position::new(1., 0.), 10.),
+        None
+    );
+}
+
+#[test]
+fn nearest_edge_matches_brute_force() {
+    let mut rng = common::rng();
+    let index = SpatialIndex::from_polygons(&random_polygons(&mut rng));
+    for _ in 0..500 {
+        let point = Position::new(rng.gen_range(-120. ..120.), rng.gen_range(-120. ..120.));
+        let hit = index.nearest_edge(point).unwrap();
+        assert!((hit.distance - brute_nearest(&index, point)).abs() < 1e-9);
+        let actual = (hit.point.x - point.x).hypot(hit.point.y - point.y);
+        assert!((actual - hit.distance).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn edges_in_rect() {
+    let level = Level::new();
+    let index = SpatialIndex::new(&level);
+    let found = index.edges_in_rect(Position::new(-1., 2.), Position::new(3., 3.));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].from, Position::new(0., 7.));
+    assert_eq!(found[0].to, Position::new(0., 0.));
+    assert_eq!(
+        index
+            .edges_in_rect(Position::new(2., 2.), Position::new(3., 3.))
+            .len(),
+        0
+    );
+    assert_eq!(
+        index
+            .edges_in_rect(Position::new(-5., -5.), Position::new(15., 15.))
+            .len(),
+        4
+    );
+    // Diagonal edge passing by the corner of the rectangle.
+    let index = SpatialIndex::from_polygons(&[polygon(false, &[(0., 0.), (4., 4.), (0., 4.)])]);
+    let found = index.edges_in_rect(Position::new(2.5, 0.), Position::new(4., 2.));
+    assert_eq!(found.len(), 0);
+    let found = index.edges_in_rect(Position::new(1.5, 0.), Position::new(4., 2.));
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].vertex, 0);
+}
+
+#[test]
+fn raycast_matches_brute_force() {
+    let mut rng = common::rng();
+    let index = SpatialIndex::from_polygons(&random_polygons(&mut rng));
+    for _ in 0..500 {
+        let origin = Position::new(rng.gen_range(-100. ..100.), rng.gen_range(-100. ..100.));
+        let angle: f64 = rng.gen_range(0. ..2. * ::std::f64::consts::PI);
+        let direction = Position::new(angle.cos(), angle.sin());
+        let max_distance = rng.gen_range(1. ..200.);
+
+        let hit = index.raycast(origin, direction, max_distance);
+        let expected = brute_raycast(&index, origin, direction).filter(|&t| t <= max_distance);
+        match (hit, expected) {
+            (Some(hit), Some(t)) => {
+                assert!((hit.distance - t).abs() < 1e-9);
+                assert!(brute_nearest(&index, hit.point) < 1e-9);
+            }
+            (None, None) => {}
+            _ => panic!("{:?} != {:?}", hit, expected),
+        }
+    }
+}
+
+#[test]
+fn raycast_along_axes() {
+    let index = SpatialIndex::new(&Level::new());
+    let from = Position::new(3., 3.);
+    let cases = [
+        (Position::new(1., 0.), Position::new(10., 3.)),
+        (Position::new(-2., 0.), Position::new(0., 3.)),
+        (Position::new(0., 1.), Position::new(3., 7.)),
+        (Position::new(0., -1.), Position::new(3., 0.)),
+    ];
+    for &(direction, point) in &cases {
+        assert_eq!(index.raycast(from, direction, 100.).unwrap().point, point);
+    }
+    assert_eq!(index.raycast(from, Position::new(1., 0.), 6.), None);
+    // Starting outside the level.
+    let hit = index
+        .raycast(Position::new(-20., 3.5), Position::new(1., 0.), 100.)
+        .unwrap();
+    assert_eq!(hit.point, Position::new(0., 3.5));
+    assert_eq!(hit.distance, 20.);
+    assert_eq!(
+        index.raycast(Position::new(-20., 30.), Position::new(1., 0.), 100.),
+        None
+    );
+}
+
+#[test]
+fn drop_object_onto_ground() {
+    // A floor with a slope and a spike.
+    let index = SpatialIndex::from_polygons(&[polygon(
+        false,
+        &[
+            (0., 0.),
+            (10., 0.),
+            (20., 10.),
+            (25., 10.),
+            (26., 12.),
+            (27., 10.),
+            (40., 10.),
+            (40., 30.),
+            (0., 30.),
+        ],
+    )]);
+    let mut object = Object::new();
+
+    object.position = Position::new(5., 20.);
+    assert!((index.drop_object(&mut object).unwrap() - 19.6).abs() < 1e-9);
+    assert!((object.position.y - 0.4).abs() < 1e-9);
+    // Already resting.
+    assert!(index.drop_object(&mut object).unwrap().abs() < 1e-9);
+
+    // On the 45 degree slope the circle rests with its centre r * sqrt(2) above the surface.
+    object.position = Position::new(15., 20.);
+    index.drop_object(&mut object).unwrap();
+    let expected = 5. + 0.4 * 2_f64.sqrt();
+    assert!((object.position.y - expected).abs() < 1e-9);
+
+    // Right above the spike's tip.
+    object.position = Position::new(26., 20.);
+    index.drop_object(&mut object).unwrap();
+    assert!((object.position.y - 12.4).abs() < 1e-9);
+
+    // Below the ground there is nothing to land on.
+    object.position = Position::new(5., -5.);
+    assert_eq!(index.drop_object(&mut object), None);
+    assert_eq!(object.position, Position::new(5., -5.));
+}
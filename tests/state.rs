@@ -1,5 +1,8 @@
 extern crate elma;
 extern crate nom;
+extern crate rand;
+
+mod common;
 
 use elma::state::*;
 use elma::{BestTimes, TimeEntry};
@@ -84,3 +87,32 @@ fn state_5_skips_max_lev_tag() {
     );
     assert_eq!(state.players[0].last_internal, 8);
 }
+
+#[test]
+fn read_from_and_write_to_state() {
+    let file = fs::File::open("tests/assets/state/state.dat").unwrap();
+    let state = State::read_from(file).unwrap();
+    assert_eq!(state.path, None);
+
+    let mut buffer = vec![];
+    state.write_to(&mut buffer).unwrap();
+    assert_eq!(buffer, fs::read("tests/assets/state/state.dat").unwrap());
+}
+
+#[test]
+fn truncated_state() {
+    let buffer = fs::read("tests/assets/state/state.dat").unwrap();
+    assert_eq!(
+        State::from_bytes(&buffer[..1000]).unwrap_err(),
+        elma::ElmaError::UnexpectedEof {
+            offset: 4,
+            section: "state",
+        }
+    );
+}
+
+#[test]
+fn damaged_state_does_not_panic() {
+    let original = fs::read("tests/assets/state/state.dat").unwrap();
+    common::check_damaged(&original, 200, |buffer| State::from_bytes(buffer));
+}
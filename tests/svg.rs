@@ -0,0 +1,178 @@
+extern crate elma;
+
+use elma::lev::*;
+use elma::svg::*;
+use elma::{ElmaError, Position};
+
+fn options(scale: f64, tolerance: f64) -> SvgOptions {
+    SvgOptions { scale, tolerance }
+}
+
+fn distance(a: &Position<f64>, b: &Position<f64>) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[test]
+fn rectangle_with_group_transform() {
+    let svg = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Created with Inkscape -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
+  <defs><rect width="5" height="5"/></defs>
+  <g inkscape:label="Layer 1" transform="translate(10,20)">
+    <rect x="0" y="0" width="30" height="10"/>
+  </g>
+</svg>"#;
+    let import = import_svg(svg, &options(0.5, 0.01)).unwrap();
+    assert_eq!(import.objects.len(), 0);
+    assert_eq!(import.polygons.len(), 1);
+    assert_eq!(
+        import.polygons[0].vertices,
+        vec![
+            Position::new(5., -10.),
+            Position::new(20., -10.),
+            Position::new(20., -15.),
+            Position::new(5., -15.),
+        ]
+    );
+}
+
+#[test]
+fn path_commands_and_number_formats() {
+    let svg = r#"<svg>
+  <path d="m0,0l10-0 0 10-10 0z M20 0h5v5H20zM.5.5L1e1.5 10,1.5"/>
+</svg>"#;
+    let import = import_svg(svg, &options(1., 0.01)).unwrap();
+    let counts: Vec<_> = import.polygons.iter().map(|p| p.vertices.len()).collect();
+    assert_eq!(counts, [4, 4, 3]);
+    assert_eq!(import.polygons[0].vertices[2], Position::new(10., -10.));
+    assert_eq!(import.polygons[1].vertices[1], Position::new(25., 0.));
+    assert_eq!(import.polygons[2].vertices[0], Position::new(0.5, -0.5));
+    assert_eq!(import.polygons[2].vertices[1], Position::new(10., -0.5));
+}
+
+#[test]
+fn curves_are_flattened_within_tolerance() {
+    let svg = r#"<svg><path d="M0 0 C 0 -40 40 -40 40 0 Z"/></svg>"#;
+    let coarse = import_svg(svg, &options(1., 1.)).unwrap();
+    let fine = import_svg(svg, &options(1., 0.01)).unwrap();
+    assert!(fine.polygons[0].vertices.len() > coarse.polygons[0].vertices.len());
+
+    // Every vertex lies on the curve, y(t) = 120t(1 - t) after flipping.
+    for vertex in &fine.polygons[0].vertices {
+        if vertex.y == 0. {
+            continue;
+        }
+        let t = (1. - (1. - vertex.y / 30.).sqrt()) / 2.;
+        let x = 120. * t * t - 80. * t * t * t;
+        let x_mirrored = 40. - x;
+        assert!((vertex.x - x).abs() < 1e-9 || (vertex.x - x_mirrored).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn arcs_and_circles() {
+    let svg = r#"<svg>
+  <path d="M 0 0 A 10 10 0 0 1 20 0 Z"/>
+  <circle cx="50" cy="50" r="10"/>
+</svg>"#;
+    let tolerance = 0.01;
+    let import = import_svg(svg, &options(1., tolerance)).unwrap();
+    assert_eq!(import.polygons.len(), 2);
+
+    let centre = Position::new(10., 0.);
+    let half = &import.polygons[0].vertices;
+    assert!(half
+        .iter()
+        .all(|v| (distance(v, &centre) - 10.).abs() < 1e-9));
+    // Sweep flag 1 goes clockwise on screen, which is above the chord in level coordinates.
+    assert!(half.iter().all(|v| v.y >= -1e-9));
+
+    let centre = Position::new(50., -50.);
+    let circle = &import.polygons[1].vertices;
+    assert!(circle.len() > 16);
+    assert!(circle
+        .iter()
+        .all(|v| (distance(v, &centre) - 10.).abs() < 1e-9));
+    for pair in circle.windows(2) {
+        let mid = Position::new((pair[0].x + pair[1].x) / 2., (pair[0].y + pair[1].y) / 2.);
+        assert!(10. - distance(&mid, &centre) <= tolerance);
+    }
+}
+
+#[test]
+fn tagged_objects_and_grass() {
+    let svg = r#"<svg xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape">
+  <path d="M 0 0 H 100 V 70 H 0 Z"/>
+  <path id="grass1" d="M 0 60 H 100 V 70 H 0 Z"/>
+  <circle id="start" cx="20" cy="60" r="4"/>
+  <circle class="big exit" cx="80" cy="60" r="4"/>
+  <g inkscape:label="Apples" transform="scale(2)">
+    <circle cx="10" cy="10" r="2"/>
+    <ellipse cx="20" cy="10" rx="2" ry="1"/>
+  </g>
+  <circle id="killer" cx="50" cy="30" r="4" style="display: none"/>
+</svg>"#;
+    let import = import_svg(svg, &SvgOptions::new()).unwrap();
+    assert_eq!(import.polygons.len(), 2);
+    assert!(!import.polygons[0].grass);
+    assert!(import.polygons[1].grass);
+
+    let objects: Vec<_> = import
+        .objects
+        .iter()
+        .map(|o| (o.position.x, o.position.y, o.is_apple(), o.is_player()))
+        .collect();
+    assert_eq!(
+        objects,
+        [
+            (2., -6., false, true),
+            (8., -6., false, false),
+            (2., -2., true, false),
+            (4., -2., true, false),
+        ]
+    );
+    assert_eq!(import.objects[1].object_type, ObjectType::Exit);
+
+    let level = import.into_level();
+    assert!(level.check_topology().is_ok());
+}
+
+#[test]
+fn invalid_svg() {
+    let invalid = |svg: &str| import_svg(svg, &SvgOptions::new()).unwrap_err();
+    assert_eq!(
+        invalid("<svg><g></svg>"),
+        ElmaError::InvalidSvg(SvgError::InvalidXml(8))
+    );
+    assert_eq!(
+        invalid("<svg><path d='M 0 0 L'/></svg>"),
+        ElmaError::InvalidSvg(SvgError::InvalidPathData("M 0 0 L".into()))
+    );
+    assert_eq!(
+        invalid("<svg><rect width='1cm' height='1'/></svg>"),
+        ElmaError::InvalidSvg(SvgError::InvalidNumber("1cm".into()))
+    );
+    assert_eq!(
+        invalid("<svg><g transform='spin(4)'></g></svg>"),
+        ElmaError::InvalidSvg(SvgError::InvalidTransform("spin(4)".into()))
+    );
+    assert_eq!(
+        invalid("<svg><g"),
+        ElmaError::InvalidSvg(SvgError::InvalidXml(5))
+    );
+}
+
+#[test]
+fn rounded_rectangle() {
+    let svg = r#"<svg><rect x="0" y="0" width="20" height="10" rx="3"/></svg>"#;
+    let import = import_svg(svg, &options(1., 0.01)).unwrap();
+    let vertices = &import.polygons[0].vertices;
+    assert!(vertices.len() > 8);
+    assert!(vertices
+        .iter()
+        .all(|v| v.x >= -1e-9 && v.x <= 20. + 1e-9 && v.y <= 1e-9 && v.y >= -10. - 1e-9));
+    // Corners are cut off.
+    assert!(vertices
+        .iter()
+        .all(|v| distance(v, &Position::new(0., 0.)) > 0.8));
+}
@@ -0,0 +1,81 @@
+extern crate elma;
+
+use elma::geometry::{area, contains};
+use elma::lev::*;
+use elma::text::*;
+use elma::Position;
+
+fn bounds(polygons: &[Polygon]) -> (f64, f64, f64, f64) {
+    let mut bounds = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for vertex in polygons.iter().flat_map(|p| &p.vertices) {
+        bounds.0 = bounds.0.min(vertex.x);
+        bounds.1 = bounds.1.min(vertex.y);
+        bounds.2 = bounds.2.max(vertex.x);
+        bounds.3 = bounds.3.max(vertex.y);
+    }
+    bounds
+}
+
+#[test]
+fn single_letter_outline() {
+    let polygons = text_polygons("I", Position::new(0., 0.), &TextStyle::new());
+    assert_eq!(polygons.len(), 1);
+    assert!(!polygons[0].grass);
+    let (min_x, min_y, max_x, max_y) = bounds(&polygons);
+    // Size is the cap height, strokes stick out by half their thickness.
+    assert!((max_y - min_y - 2.3).abs() < 1e-9);
+    assert!((max_x - min_x - (2. / 3. + 0.3)).abs() < 1e-9);
+}
+
+#[test]
+fn enclosed_letter_has_hole() {
+    let polygons = text_polygons("o", Position::new(0., 0.), &TextStyle::new());
+    assert_eq!(polygons.len(), 2);
+    assert!(!contains(&polygons, &Position::new(2. / 3., 1.)));
+    assert!(contains(&polygons, &Position::new(0., 1.)));
+}
+
+#[test]
+fn grass_text() {
+    let style = TextStyle {
+        grass: true,
+        ..TextStyle::new()
+    };
+    let polygons = text_polygons("ELMA 123", Position::new(5., 5.), &style);
+    assert!(!polygons.is_empty());
+    assert!(polygons.iter().all(|p| p.grass));
+    assert!(polygons.iter().all(|p| area(&p.vertices).abs() > 0.));
+}
+
+#[test]
+fn rotated_text() {
+    let style = TextStyle {
+        rotation: std::f64::consts::PI / 2.,
+        ..TextStyle::new()
+    };
+    let straight = bounds(&text_polygons(
+        "HELLO",
+        Position::new(0., 0.),
+        &TextStyle::new(),
+    ));
+    let rotated = bounds(&text_polygons("HELLO", Position::new(0., 0.), &style));
+    assert!(((straight.2 - straight.0) - (rotated.3 - rotated.1)).abs() < 1e-6);
+    assert!(((straight.3 - straight.1) - (rotated.2 - rotated.0)).abs() < 1e-6);
+}
+
+#[test]
+fn text_in_level_passes_topology() {
+    let mut level = Level::new();
+    level.polygons[0].vertices = vec![
+        Position::new(0., 0.),
+        Position::new(40., 0.),
+        Position::new(40., 10.),
+        Position::new(0., 10.),
+    ];
+    level.polygons.extend(text_polygons(
+        "GO!\nWIN",
+        Position::new(2., 6.),
+        &TextStyle::new(),
+    ));
+    assert!(level.check_topology().is_ok());
+}
@@ -28,3 +28,32 @@ fn string_null_pad_utf8_error() {
         elma::ElmaError::NonASCII
     );
 }
+
+#[test]
+/// Top10 lists with a bogus entry count or too little data.
+fn parse_top10_malformed() {
+    let mut top10 = vec![0; 344];
+    top10[0] = 11;
+    assert_eq!(
+        parse_top10(&top10).unwrap_err(),
+        elma::ElmaError::InvalidTop10Count(11)
+    );
+    assert_eq!(
+        parse_top10(&[255, 255, 255, 255]).unwrap_err(),
+        elma::ElmaError::InvalidTop10Count(-1)
+    );
+    assert_eq!(
+        parse_top10(&[1, 0]).unwrap_err(),
+        elma::ElmaError::UnexpectedEof {
+            offset: 0,
+            section: "top10",
+        }
+    );
+    assert_eq!(
+        parse_top10(&[1, 0, 0, 0, 100, 0, 0, 0]).unwrap_err(),
+        elma::ElmaError::UnexpectedEof {
+            offset: 44,
+            section: "top10",
+        }
+    );
+}